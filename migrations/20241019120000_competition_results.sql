@@ -0,0 +1,19 @@
+-- Add migration script here
+
+CREATE TABLE competition_results (
+    -- Id of the competition channel
+    competition_id INT NOT NULL,
+    -- Place b01lers finished in
+    place INT NOT NULL,
+    -- Number of teams on the scoreboard
+    team_count INT NOT NULL,
+    -- Points b01lers finished with
+    points INT NOT NULL,
+    -- Id of the channel the scoreboard screenshot was posted in, null if none was uploaded
+    -- Attachment urls are signed and expire, so the screenshot is fetched from its message when it is needed
+    scoreboard_channel_id INT,
+    -- Id of the message the scoreboard screenshot is attached to, null if none was uploaded
+    scoreboard_message_id INT,
+    PRIMARY KEY(competition_id),
+    FOREIGN KEY(competition_id) REFERENCES competition(channel_id)
+);
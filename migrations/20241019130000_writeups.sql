@@ -0,0 +1,18 @@
+-- Add migration script here
+
+CREATE TABLE writeups (
+    id INTEGER PRIMARY KEY,
+    challenge_id INT NOT NULL,
+    -- Discord id of the author of the writeup
+    user_id INT NOT NULL,
+    url TEXT NOT NULL,
+    -- message id of bonus points approval message, null if writeups are not worth points
+    approval_message_id INT,
+    -- Approval status of bonus points for this writeup
+    -- 0: pending
+    -- 1: accepted
+    -- 2: declined
+    approval_status INT NOT NULL,
+    FOREIGN KEY(challenge_id) REFERENCES challenges(id),
+    FOREIGN KEY(user_id) REFERENCES users(id)
+);
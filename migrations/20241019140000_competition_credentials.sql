@@ -0,0 +1,10 @@
+-- Add migration script here
+
+CREATE TABLE competition_credentials (
+    -- Id of the competition channel
+    competition_id INT NOT NULL,
+    -- XChaCha20Poly1305 encrypted json of the team username and password with the competition id as associated data, with the nonce appended
+    credentials BLOB NOT NULL,
+    PRIMARY KEY(competition_id),
+    FOREIGN KEY(competition_id) REFERENCES competition(channel_id)
+);
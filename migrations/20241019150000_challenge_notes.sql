@@ -0,0 +1,18 @@
+-- Add migration script here
+
+CREATE TABLE challenge_notes (
+    id INTEGER PRIMARY KEY,
+    challenge_id INT NOT NULL,
+    -- Discord id of the user who wrote the note
+    user_id INT NOT NULL,
+    content TEXT NOT NULL,
+    -- Name of the attached file, null if there is no file
+    file_name TEXT,
+    -- Contents of the attached file, null if there is no file
+    file_data BLOB,
+    FOREIGN KEY(challenge_id) REFERENCES challenges(id)
+);
+
+-- Message id of the pinned notes digest in the challenge channel, null if there is no digest
+ALTER TABLE challenges
+ADD notes_message_id INT;
@@ -0,0 +1,33 @@
+-- Add migration script here
+
+CREATE TABLE point_transactions (
+    id INTEGER PRIMARY KEY,
+    -- Discord id of the user whose points changed
+    user_id INT NOT NULL,
+    -- Change in points, negative if points were taken away
+    delta INT NOT NULL,
+    -- Reason for the change
+    -- 0: legacy, points earned before the ledger existed
+    -- 1: message
+    -- 2: solve
+    -- 3: manual grant
+    -- 4: writeup
+    -- 5: revocation
+    reason INT NOT NULL,
+    -- Id of the solve or writeup the points were given for, null for other reasons
+    reference_id INT,
+    -- Discord id of the user who made the change, like the officer who approved a solve, null if automatic
+    actor_id INT,
+    -- Explanation given for manual changes
+    note TEXT,
+    -- Unix timestamp of when the change happened
+    created_at INT NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+CREATE INDEX point_transactions_user_id ON point_transactions(user_id);
+
+-- Points from before the ledger existed can't be explained, so record them as a single legacy change
+-- They were earned at an unknown time, so they are dated at the unix epoch to keep them out of every time window
+INSERT INTO point_transactions (user_id, delta, reason, created_at)
+SELECT id, points, 0, 0 FROM users WHERE points != 0;
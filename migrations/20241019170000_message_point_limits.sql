@@ -0,0 +1,13 @@
+-- Add migration script here
+
+CREATE TABLE message_point_limits (
+    -- Discord id of the user
+    user_id INT NOT NULL,
+    -- Day that points_today was counted on, as days since the unix epoch in UTC
+    day INT NOT NULL,
+    -- Points earned from messages during the day, used for the daily cap
+    points_today INT NOT NULL,
+    -- Unix timestamp of the last message that earned points, used for the cooldown
+    last_awarded_at INT NOT NULL,
+    PRIMARY KEY(user_id)
+);
@@ -0,0 +1,28 @@
+-- Add migration script here
+
+CREATE TABLE seasons (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    -- Unix timestamp of when the season starts, points earned from then on count towards it
+    start_at INT NOT NULL,
+    -- Unix timestamp of when the season ends, null if it runs until it is closed
+    end_at INT,
+    -- Unix timestamp of when the season was closed, null while the season is running
+    closed_at INT
+);
+
+-- Final standings of a season, saved when the season is closed
+CREATE TABLE season_standings (
+    season_id INT NOT NULL,
+    user_id INT NOT NULL,
+    -- Points earned during the season
+    points INT NOT NULL,
+    -- 1 for the user with the most points
+    placement INT NOT NULL,
+    PRIMARY KEY(season_id, user_id),
+    FOREIGN KEY(season_id) REFERENCES seasons(id),
+    FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+-- Points the user had when ranks were last reset at the end of a season, only points earned since count towards their rank
+ALTER TABLE users ADD COLUMN rank_reset_points INT NOT NULL DEFAULT 0;
@@ -0,0 +1,49 @@
+-- Add migration script here
+
+-- Reactions which have already been given points, so removing and adding a reaction again isn't worth more points
+CREATE TABLE reaction_points (
+    message_id INT NOT NULL,
+    -- Discord id of the user who reacted
+    user_id INT NOT NULL,
+    PRIMARY KEY(message_id, user_id)
+);
+
+-- Meetings where members can check in with a code for attendance points
+CREATE TABLE meetings (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- Code members enter to check in
+    code TEXT NOT NULL,
+    -- Discord id of the officer who opened check in
+    opened_by INT NOT NULL,
+    -- Unix timestamp of when check in opened
+    opened_at INT NOT NULL,
+    -- Unix timestamp after which the code no longer works
+    closes_at INT NOT NULL
+);
+
+CREATE TABLE meeting_attendance (
+    meeting_id INT NOT NULL,
+    user_id INT NOT NULL,
+    PRIMARY KEY(meeting_id, user_id),
+    FOREIGN KEY(meeting_id) REFERENCES meetings(id),
+    FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+-- Time spent in voice channels worth points, saved so time in voice isn't lost when the bot restarts
+CREATE TABLE voice_sessions (
+    id INTEGER PRIMARY KEY,
+    -- Discord id of the user in voice
+    user_id INT NOT NULL,
+    -- Unix timestamp of when the user joined voice
+    joined_at INT NOT NULL,
+    -- Unix timestamp up to which time in voice has already been checked for points
+    counted_until INT NOT NULL,
+    -- Unix timestamp of when the user left voice, null while they are still in voice
+    left_at INT
+);
+
+CREATE INDEX voice_sessions_open ON voice_sessions(user_id) WHERE left_at IS NULL;
+
+-- Points for voice are recorded in point_transactions with reason 6 and the id of the voice session in reference_id,
+-- reactions with reason 7 and the id of the message, and attendance with reason 8 and the id of the meeting
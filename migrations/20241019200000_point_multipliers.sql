@@ -0,0 +1,12 @@
+-- Add migration script here
+
+-- Temporary multipliers for message points set by officers, like a double points weekend
+CREATE TABLE point_multipliers (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    multiplier REAL NOT NULL,
+    -- Discord id of the officer who started the multiplier
+    created_by INT NOT NULL,
+    -- Unix timestamp after which the multiplier no longer applies
+    expires_at INT NOT NULL
+);
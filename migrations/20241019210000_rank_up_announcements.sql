@@ -0,0 +1,4 @@
+-- Add migration script here
+
+-- Whether rank ups are announced publicly, users can opt out with `/ranks announcements`
+ALTER TABLE users ADD COLUMN announce_rank_ups BOOLEAN NOT NULL DEFAULT TRUE;
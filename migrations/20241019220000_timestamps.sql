@@ -0,0 +1,27 @@
+-- Add migration script here
+
+-- Discord snowflakes store the milliseconds since the start of 2015 in their upper bits,
+-- so existing rows get the time their channel or message was created
+
+-- Unix timestamp of when the competition was created
+ALTER TABLE competition ADD COLUMN created_at INT NOT NULL DEFAULT 0;
+UPDATE competition SET created_at = ((channel_id >> 22) + 1420070400000) / 1000;
+
+-- Unix timestamp of when the challenge was created, challenges without a channel use the competition channel
+ALTER TABLE challenges ADD COLUMN created_at INT NOT NULL DEFAULT 0;
+UPDATE challenges SET created_at = ((COALESCE(channel_id, competition_id) >> 22) + 1420070400000) / 1000;
+
+-- Unix timestamp of when the solve was submitted
+ALTER TABLE solves ADD COLUMN created_at INT NOT NULL DEFAULT 0;
+UPDATE solves SET created_at = ((approval_message_id >> 22) + 1420070400000) / 1000;
+-- Unix timestamp of when the solve was approved, null if it is not approved or was approved before this was recorded
+ALTER TABLE solves ADD COLUMN approved_at INT;
+
+-- Unix timestamp of when the bot first saw the user, existing users use their oldest point change
+ALTER TABLE users ADD COLUMN created_at INT NOT NULL DEFAULT 0;
+UPDATE users SET created_at = COALESCE(
+    (SELECT MIN(created_at) FROM point_transactions WHERE point_transactions.user_id = users.id),
+    unixepoch()
+);
+-- Unix timestamp of when the user verified their email, null if not verified or verified before this was recorded
+ALTER TABLE users ADD COLUMN verified_at INT;
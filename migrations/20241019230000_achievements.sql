@@ -0,0 +1,22 @@
+-- Add migration script here
+
+CREATE TABLE user_achievements (
+    id INTEGER PRIMARY KEY,
+    -- Discord id of the user who earned the achievement
+    user_id INT NOT NULL,
+    -- Kind of achievement
+    -- 0: first approved solve in a category
+    -- 1: first blood in a competition
+    -- 2: solve milestone
+    -- 3: solved every category in a competition
+    -- 4: completed a bingo line in a competition
+    achievement INT NOT NULL,
+    -- Category, competition channel id, or number of solves depending on the kind of achievement
+    reference_id INT NOT NULL,
+    -- Unix timestamp of when the achievement was earned
+    earned_at INT NOT NULL,
+    UNIQUE(user_id, achievement, reference_id)
+);
+
+-- Bonus points for achievements are recorded in point_transactions with reason 9,
+-- and the id of the achievement in reference_id
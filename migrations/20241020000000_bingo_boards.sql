@@ -0,0 +1,5 @@
+-- Add migration script here
+
+-- Name of the bingo board from config the competition uses, null for the default board
+ALTER TABLE competition
+ADD bingo_board TEXT;
@@ -0,0 +1,5 @@
+-- Add migration script here
+
+-- Bitfield specifying which bonus squares of the bingo board have been achieved
+ALTER TABLE competition
+ADD bingo_bonus INT NOT NULL DEFAULT 0;
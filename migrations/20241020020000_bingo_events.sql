@@ -0,0 +1,20 @@
+-- Add migration script here
+
+CREATE TABLE bingo_events (
+    id INTEGER PRIMARY KEY,
+    -- Channel id of the competition the square was marked in
+    competition_id INT NOT NULL,
+    -- Name of the bingo board in config the competition was using
+    board TEXT NOT NULL,
+    -- Label of the square, kept as text so history survives boards being edited
+    square TEXT NOT NULL,
+    -- 1 if the square was marked, 0 if it was unmarked
+    marked INT NOT NULL,
+    -- Discord id of the user who changed the square
+    user_id INT NOT NULL,
+    -- Unix timestamp of when the square was changed
+    created_at INT NOT NULL,
+    FOREIGN KEY(competition_id) REFERENCES competition(channel_id)
+);
+
+CREATE INDEX bingo_events_competition_id ON bingo_events(competition_id);
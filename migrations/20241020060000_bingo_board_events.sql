@@ -0,0 +1,5 @@
+-- Add migration script here
+
+-- 1 if the competition switched to `board` and its marks were cleared, 0 if a square was marked or unmarked
+-- square is empty and marked is 0 for board changes
+ALTER TABLE bingo_events ADD COLUMN board_changed INT NOT NULL DEFAULT 0;
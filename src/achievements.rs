@@ -0,0 +1,161 @@
+//! Achievements earned for solving challenges and playing in competitions, announced in the rank up channel
+
+use std::collections::HashSet;
+
+use serenity::all::{Context, CreateMessage, UserId};
+use tracing::error;
+
+use crate::bingo::BingoCard;
+use crate::config::config;
+use crate::db::{Achievement, ChallengeType, Competition, DbConn, PointsReason, Solve, UserAchievement};
+use crate::points::{give_points, points_to_string};
+
+/// Numbers of distinct challenges solved which earn a milestone achievement
+const SOLVE_MILESTONES: [i64; 3] = [10, 50, 100];
+
+/// Gets the bonus points for earning the achievement, `None` if it is not worth points
+fn achievement_points(achievement: &Achievement) -> Option<i64> {
+    let points = &config().achievement_points;
+
+    match achievement {
+        Achievement::FirstCategorySolve { .. } => points.first_category_solve,
+        Achievement::FirstBlood { .. } => points.first_blood,
+        Achievement::SolveMilestone { .. } => points.solve_milestone,
+        Achievement::AllCategories { .. } => points.all_categories,
+        Achievement::BingoLine { .. } => points.bingo_line,
+    }
+}
+
+/// Awards the achievements earned by the solvers of a solve which was just approved
+///
+/// The solve must already be saved as approved.
+pub async fn check_solve_achievements(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    solve: &Solve,
+    solver_ids: &[UserId],
+) -> anyhow::Result<()> {
+    let challenge = db.get_challenge_by_id(solve.challenge_id).await?;
+    let competition_id = challenge.competition_id;
+
+    let competition_categories: HashSet<ChallengeType> = db.get_challenges_for_competition(competition_id).await?
+        .iter()
+        .map(|challenge| challenge.category)
+        .collect();
+
+    let is_first_blood = db.get_first_blood_solve_id(competition_id).await? == Some(solve.id);
+
+    // the line may have been completed before this was anyone's first solve in the competition
+    let competition = db.get_competition(competition_id).await?;
+    let has_bingo_line = BingoCard::for_competition(&competition)?.has_line();
+
+    for user_id in solver_ids {
+        let solves = db.get_solved_challenges_for_user(*user_id).await?;
+
+        let mut earned = vec![Achievement::FirstCategorySolve { category: challenge.category }];
+
+        if is_first_blood {
+            earned.push(Achievement::FirstBlood { competition_id });
+        }
+
+        if has_bingo_line {
+            earned.push(Achievement::BingoLine { competition_id });
+        }
+
+        let solved_challenge_count = solves.iter()
+            .map(|solve| solve.id)
+            .collect::<HashSet<_>>()
+            .len() as i64;
+
+        for milestone in SOLVE_MILESTONES {
+            if solved_challenge_count >= milestone {
+                earned.push(Achievement::SolveMilestone { solves: milestone });
+            }
+        }
+
+        let solved_categories: HashSet<ChallengeType> = solves.iter()
+            .filter(|solve| solve.competition_id == competition_id)
+            .map(|solve| solve.category)
+            .collect();
+
+        // a competition with one category would make this the same as solving anything
+        if competition_categories.len() > 1 && competition_categories.is_subset(&solved_categories) {
+            earned.push(Achievement::AllCategories { competition_id });
+        }
+
+        for achievement in earned {
+            award_achievement(context, db, *user_id, achievement).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Awards everyone who solved a challenge in the competition, if the competition has a completed bingo line
+pub async fn check_bingo_achievements(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    competition: &Competition,
+) -> anyhow::Result<()> {
+    if !BingoCard::for_competition(competition)?.has_line() {
+        return Ok(());
+    }
+
+    let players = db.get_top_solvers_for_competition(competition.channel_id, u32::MAX).await?
+        .into_iter()
+        .map(|solver| solver.user_id);
+
+    for user_id in players {
+        award_achievement(context, db, user_id, Achievement::BingoLine { competition_id: competition.channel_id }).await?;
+    }
+
+    Ok(())
+}
+
+/// Gives the user the achievement and its bonus points, and announces it after `db` is committed if they didn't already have it
+async fn award_achievement(context: &Context, db: &mut DbConn<'_>, user_id: UserId, achievement: Achievement) -> anyhow::Result<()> {
+    let Some(user_achievement) = db.award_achievement(user_id, achievement).await? else {
+        return Ok(());
+    };
+
+    let points = achievement_points(&achievement);
+    if let Some(points) = points {
+        let reason = PointsReason::Achievement { achievement_id: user_achievement.id };
+        give_points(context, db, user_id, points, &reason, None).await?;
+    }
+
+    // people who have never earned points haven't opted out
+    if let Ok(db_user) = db.get_user_by_id(user_id).await {
+        if !db_user.announce_rank_ups {
+            return Ok(());
+        }
+    }
+
+    let context = context.clone();
+    db.after_commit(async move {
+        // a failed announcement shouldn't take away the achievement
+        if let Err(e) = announce_achievement(&context, &user_achievement, points).await {
+            error!("failed to announce achievement for {user_id}: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Posts the achievement in the rank up channel
+async fn announce_achievement(
+    context: &Context,
+    user_achievement: &UserAchievement,
+    points: Option<i64>,
+) -> anyhow::Result<()> {
+    let user = user_achievement.user_id.to_user(context).await?;
+
+    let mut content = format!("🏆 {} earned the achievement **{}**!", user.name, user_achievement.achievement);
+    if let Some(points) = points {
+        content.push_str(&format!(" (+{} points)", points_to_string(points)));
+    }
+
+    config().server.rank_up_channel.send_message(context, CreateMessage::new().content(content)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,416 @@
+//! Draws bingo boards from the square labels in config, and checks them for completed lines
+
+use std::io::Cursor;
+
+use ab_glyph::{FontRef, PxScale};
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use imageproc::{drawing::{draw_filled_rect_mut, draw_polygon_mut, draw_text_mut, text_size, Blend, Canvas}, point::Point, rect::Rect};
+
+use crate::config::{config, BingoBoardConfig};
+use crate::db::Competition;
+use crate::fonts::{renderable_text, BOLD_FONT, FONT};
+
+const CELL_SIZE: u32 = 180;
+const MARGIN: u32 = 20;
+const TITLE_HEIGHT: u32 = 100;
+/// Space above the bonus rows for the bonus label
+const BONUS_HEADER_HEIGHT: u32 = 70;
+const BONUS_CELL_HEIGHT: u32 = 110;
+const GRID_THICKNESS: u32 = 4;
+/// Space between the text in a square and the grid lines
+const CELL_PADDING: u32 = 12;
+
+const TITLE_SCALE: f32 = 64.0;
+/// Sizes tried for square labels, largest first, until the label fits in its square
+const LABEL_SCALES: [f32; 6] = [30.0, 26.0, 22.0, 19.0, 16.0, 13.0];
+const FREE_SCALE: f32 = 36.0;
+const BONUS_SCALE: f32 = 36.0;
+
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const TEXT: Rgba<u8> = Rgba([0, 0, 0, 255]);
+/// Red used for the free square
+const FREE_TEXT: Rgba<u8> = Rgba([0xe0, 0x10, 0x10, 255]);
+/// See through red used for marks and lines, so the labels under them can still be read
+const MARK: Rgba<u8> = Rgba([0xe0, 0x10, 0x10, 170]);
+
+/// A square on a bingo board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BingoSquare {
+    /// Square on the grid, counted from the top left row by row
+    Board(usize),
+    /// Square in the bonus rows under the grid, which don't count towards lines
+    Bonus(usize),
+}
+
+/// A bingo board with the squares marked for a competition
+pub struct BingoCard<'a> {
+    pub board: &'a BingoBoardConfig,
+    /// Bit `i` is set if square `i` is marked, the free square is always marked
+    pub marks: u64,
+    /// Bit `i` is set if bonus square `i` is marked
+    pub bonus_marks: u64,
+}
+
+/// Normalizes a square label so labels which only differ in case are the same square
+pub fn normalize_label(label: &str) -> String {
+    label.to_lowercase()
+}
+
+/// Gets the name of the board in config the competition uses
+pub fn board_name(competition: &Competition) -> &str {
+    competition.bingo_board.as_ref().unwrap_or(&config().bingo.default_board)
+}
+
+impl BingoCard<'static> {
+    /// Gets the board the competition uses, with its marks
+    pub fn for_competition(competition: &Competition) -> anyhow::Result<Self> {
+        let name = board_name(competition);
+
+        let board = config().bingo.boards.get(name)
+            .ok_or_else(|| anyhow::anyhow!("The bingo board {name} no longer exists, pick a new one with `/bingo board`."))?;
+
+        Ok(BingoCard {
+            board,
+            marks: competition.bingo,
+            bonus_marks: competition.bingo_bonus,
+        })
+    }
+}
+
+/// Area of the image taken up by a square
+struct Cell {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Cell {
+    fn center(&self) -> (f32, f32) {
+        ((self.x + self.width / 2) as f32, (self.y + self.height / 2) as f32)
+    }
+}
+
+impl BingoCard<'_> {
+    /// Checks if the square is the free square, which is always marked
+    pub fn is_free(&self, square: BingoSquare) -> bool {
+        let BingoSquare::Board(i) = square else {
+            return false;
+        };
+
+        self.board.free_square.as_ref() == Some(&self.board.squares[i])
+    }
+
+    pub fn is_marked(&self, square: BingoSquare) -> bool {
+        match square {
+            BingoSquare::Board(i) => self.marks & (1 << i) != 0 || self.is_free(square),
+            BingoSquare::Bonus(i) => self.bonus_marks & (1 << i) != 0,
+        }
+    }
+
+    pub fn label(&self, square: BingoSquare) -> &str {
+        match square {
+            BingoSquare::Board(i) => &self.board.squares[i],
+            BingoSquare::Bonus(i) => &self.board.bonus_squares[i],
+        }
+    }
+
+    /// Finds the square with the label, ignoring case
+    pub fn find_square(&self, label: &str) -> Option<BingoSquare> {
+        let label = normalize_label(label);
+        let board_square = self.board.squares.iter()
+            .position(|square| normalize_label(square) == label)
+            .map(BingoSquare::Board);
+        let bonus_square = || self.board.bonus_squares.iter()
+            .position(|square| normalize_label(square) == label)
+            .map(BingoSquare::Bonus);
+
+        board_square.or_else(bonus_square)
+    }
+
+    /// Gets the squares which can be marked or unmarked, with their labels
+    ///
+    /// The free square can't be changed so it is left out.
+    pub fn squares(&self, marked: bool) -> impl Iterator<Item = (BingoSquare, &str)> {
+        self.all_squares()
+            .filter(move |(square, _)| !self.is_free(*square) && self.is_marked(*square) == marked)
+    }
+
+    /// Gets every square on the board followed by the bonus squares, with their labels
+    fn all_squares(&self) -> impl Iterator<Item = (BingoSquare, &str)> {
+        let board_squares = self.board.squares.iter()
+            .enumerate()
+            .map(|(i, square)| (BingoSquare::Board(i), square.as_str()));
+        let bonus_squares = self.board.bonus_squares.iter()
+            .enumerate()
+            .map(|(i, square)| (BingoSquare::Bonus(i), square.as_str()));
+
+        board_squares.chain(bonus_squares)
+    }
+
+    /// Gets the first and last square of every completely marked row, column, and diagonal
+    pub fn completed_lines(&self) -> Vec<(usize, usize)> {
+        let size = self.board.size;
+
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        for i in 0..size {
+            lines.push((0..size).map(|x| i * size + x).collect());
+            lines.push((0..size).map(|y| y * size + i).collect());
+        }
+        lines.push((0..size).map(|i| i * size + i).collect());
+        lines.push((0..size).map(|i| i * size + size - 1 - i).collect());
+
+        lines.into_iter()
+            .filter(|line| line.iter().all(|i| self.is_marked(BingoSquare::Board(*i))))
+            .map(|line| (line[0], line[line.len() - 1]))
+            .collect()
+    }
+
+    pub fn has_line(&self) -> bool {
+        !self.completed_lines().is_empty()
+    }
+
+    fn grid_size(&self) -> u32 {
+        self.board.size as u32 * CELL_SIZE
+    }
+
+    /// Number of bonus rows, each has at most as many squares as a row of the grid
+    fn bonus_row_count(&self) -> u32 {
+        self.board.bonus_squares.len().div_ceil(self.board.size) as u32
+    }
+
+    fn cell(&self, square: BingoSquare) -> Cell {
+        let size = self.board.size;
+
+        match square {
+            BingoSquare::Board(i) => Cell {
+                x: MARGIN + (i % size) as u32 * CELL_SIZE,
+                y: TITLE_HEIGHT + (i / size) as u32 * CELL_SIZE,
+                width: CELL_SIZE,
+                height: CELL_SIZE,
+            },
+            BingoSquare::Bonus(i) => {
+                // bonus squares in a row share its width evenly
+                let row = i / size;
+                let row_length = (self.board.bonus_squares.len() - row * size).min(size) as u32;
+                let column = (i % size) as u32;
+
+                let left = column * self.grid_size() / row_length;
+                let right = (column + 1) * self.grid_size() / row_length;
+
+                Cell {
+                    x: MARGIN + left,
+                    y: TITLE_HEIGHT + self.grid_size() + BONUS_HEADER_HEIGHT + row as u32 * BONUS_CELL_HEIGHT,
+                    width: right - left,
+                    height: BONUS_CELL_HEIGHT,
+                }
+            },
+        }
+    }
+
+    pub fn render_png_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let font = FontRef::try_from_slice(FONT)?;
+        let bold_font = FontRef::try_from_slice(BOLD_FONT)?;
+
+        let width = self.grid_size() + 2 * MARGIN;
+        let mut height = TITLE_HEIGHT + self.grid_size() + MARGIN;
+        if !self.board.bonus_squares.is_empty() {
+            height += BONUS_HEADER_HEIGHT + self.bonus_row_count() * BONUS_CELL_HEIGHT;
+        }
+
+        let mut image = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+        // shrink long titles to fit
+        let title = renderable_text(&bold_font, &self.board.title);
+        let mut title_scale = PxScale::from(TITLE_SCALE);
+        while text_size(title_scale, &bold_font, &title).0 > width - 2 * MARGIN && title_scale.x > LABEL_SCALES[LABEL_SCALES.len() - 1] {
+            title_scale = PxScale::from(title_scale.x - 4.0);
+        }
+        draw_centered_text(&mut image, &bold_font, title_scale, TEXT, &title, width / 2, TITLE_HEIGHT / 2);
+
+        if !self.board.bonus_squares.is_empty() {
+            let header_center = TITLE_HEIGHT + self.grid_size() + BONUS_HEADER_HEIGHT / 2;
+            draw_centered_text(&mut image, &bold_font, PxScale::from(BONUS_SCALE), TEXT, "BONUS", width / 2, header_center);
+        }
+
+        for (square, label) in self.all_squares() {
+            let cell = self.cell(square);
+            draw_cell_outline(&mut image, &cell);
+            draw_label(&mut image, &font, &bold_font, &cell, label, self.is_free(square));
+        }
+
+        // marks are blended so the labels stay readable
+        let mut canvas = Blend(image);
+
+        for (square, _) in self.all_squares() {
+            // the free square is marked by its label instead of an x
+            if self.is_marked(square) && !self.is_free(square) {
+                let cell = self.cell(square);
+                let (center_x, center_y) = cell.center();
+                let reach_x = (cell.width / 2 - CELL_PADDING) as f32;
+                let reach_y = (cell.height / 2 - CELL_PADDING) as f32;
+
+                draw_thick_line(&mut canvas, (center_x - reach_x, center_y - reach_y), (center_x + reach_x, center_y + reach_y), 6.0);
+                draw_thick_line(&mut canvas, (center_x - reach_x, center_y + reach_y), (center_x + reach_x, center_y - reach_y), 6.0);
+            }
+        }
+
+        for (start, end) in self.completed_lines() {
+            let start = self.cell(BingoSquare::Board(start)).center();
+            let end = self.cell(BingoSquare::Board(end)).center();
+
+            // extend the line a bit past the centers of the end squares
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            let extend = CELL_SIZE as f32 / 3.0;
+            // a board with one square has a line with no direction
+            let (dx, dy) = if length > 0.0 { (dx / length * extend, dy / length * extend) } else { (extend, 0.0) };
+
+            draw_thick_line(&mut canvas, (start.0 - dx, start.1 - dy), (end.0 + dx, end.1 + dy), 10.0);
+        }
+
+        let mut out = Vec::new();
+        DynamicImage::ImageRgba8(canvas.0).write_to(&mut Cursor::new(&mut out), ImageFormat::Png)?;
+
+        Ok(out)
+    }
+}
+
+/// Draws text with its center at `(center_x, center_y)`
+fn draw_centered_text(image: &mut RgbaImage, font: &FontRef, scale: PxScale, color: Rgba<u8>, text: &str, center_x: u32, center_y: u32) {
+    let (text_width, text_height) = text_size(scale, font, text);
+    draw_text_mut(
+        image,
+        color,
+        center_x as i32 - text_width as i32 / 2,
+        center_y as i32 - text_height as i32 / 2,
+        scale,
+        font,
+        text,
+    );
+}
+
+/// Draws the border of a cell, centered on its edges so neighboring cells share borders
+fn draw_cell_outline(image: &mut RgbaImage, cell: &Cell) {
+    let x = cell.x as i32 - GRID_THICKNESS as i32 / 2;
+    let y = cell.y as i32 - GRID_THICKNESS as i32 / 2;
+    let width = cell.width + GRID_THICKNESS;
+    let height = cell.height + GRID_THICKNESS;
+
+    draw_filled_rect_mut(image, Rect::at(x, y).of_size(width, GRID_THICKNESS), TEXT);
+    draw_filled_rect_mut(image, Rect::at(x, y + cell.height as i32).of_size(width, GRID_THICKNESS), TEXT);
+    draw_filled_rect_mut(image, Rect::at(x, y).of_size(GRID_THICKNESS, height), TEXT);
+    draw_filled_rect_mut(image, Rect::at(x + cell.width as i32, y).of_size(GRID_THICKNESS, height), TEXT);
+}
+
+/// Draws a square's label wrapped and centered in its cell, as large as fits
+fn draw_label(image: &mut RgbaImage, font: &FontRef, bold_font: &FontRef, cell: &Cell, label: &str, is_free: bool) {
+    let (center_x, center_y) = cell.center();
+    let max_width = cell.width - 2 * CELL_PADDING;
+
+    // leave room for the free label above the square's label
+    let free_height = if is_free { text_size(PxScale::from(FREE_SCALE), bold_font, "FREE").1 + 8 } else { 0 };
+    let max_height = cell.height - 2 * CELL_PADDING - free_height;
+
+    let label = renderable_text(font, label);
+    let (scale, lines) = LABEL_SCALES.iter()
+        .map(|scale| PxScale::from(*scale))
+        .find_map(|scale| {
+            let lines = wrap_text(font, scale, &label, max_width, false)?;
+            (line_height(scale) * lines.len() as u32 <= max_height).then_some((scale, lines))
+        })
+        // fall back to splitting long words and cutting off the text if it is far too long
+        .unwrap_or_else(|| {
+            let scale = PxScale::from(LABEL_SCALES[LABEL_SCALES.len() - 1]);
+            let mut lines = wrap_text(font, scale, &label, max_width, true).unwrap_or_default();
+            lines.truncate((max_height / line_height(scale)) as usize);
+
+            (scale, lines)
+        });
+
+    let text_height = line_height(scale) * lines.len() as u32 + free_height;
+    let mut y = center_y as i32 - text_height as i32 / 2;
+
+    if is_free {
+        let (free_width, _) = text_size(PxScale::from(FREE_SCALE), bold_font, "FREE");
+        draw_text_mut(image, FREE_TEXT, center_x as i32 - free_width as i32 / 2, y, PxScale::from(FREE_SCALE), bold_font, "FREE");
+        y += free_height as i32;
+    }
+
+    for line in lines {
+        let (line_width, _) = text_size(scale, font, &line);
+        draw_text_mut(image, TEXT, center_x as i32 - line_width as i32 / 2, y, scale, font, &line);
+        y += line_height(scale) as i32;
+    }
+}
+
+fn line_height(scale: PxScale) -> u32 {
+    (scale.y * 1.15) as u32
+}
+
+/// Splits text into lines no wider than `max_width`
+///
+/// Words which are too wide are split across lines if `break_words` is set, otherwise `None` is returned.
+fn wrap_text(font: &FontRef, scale: PxScale, text: &str, max_width: u32, break_words: bool) -> Option<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if text_size(scale, font, word).0 > max_width {
+            if !break_words {
+                return None;
+            }
+
+            // start the word on a new line and split it wherever it reaches the edge
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            for c in word.chars() {
+                let candidate = format!("{line}{c}");
+                if !line.is_empty() && text_size(scale, font, &candidate).0 > max_width {
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+
+            continue;
+        }
+
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+        if text_size(scale, font, &candidate).0 <= max_width {
+            line = candidate;
+        } else {
+            lines.push(line);
+            line = word.to_string();
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    Some(lines)
+}
+
+/// Draws a line as a filled rectangle `thickness` pixels wide
+fn draw_thick_line(canvas: &mut impl Canvas<Pixel = Rgba<u8>>, start: (f32, f32), end: (f32, f32), thickness: f32) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return;
+    }
+
+    // perpendicular offset to each side of the line
+    let (nx, ny) = (-dy / length * thickness / 2.0, dx / length * thickness / 2.0);
+
+    let corners = [
+        Point::new((start.0 + nx) as i32, (start.1 + ny) as i32),
+        Point::new((end.0 + nx) as i32, (end.1 + ny) as i32),
+        Point::new((end.0 - nx) as i32, (end.1 - ny) as i32),
+        Point::new((start.0 - nx) as i32, (start.1 - ny) as i32),
+    ];
+
+    draw_polygon_mut(canvas, &corners, MARK);
+}
@@ -0,0 +1,203 @@
+//! Draws stats charts as pngs, using the same image crates as bingo
+
+use std::io::Cursor;
+
+use ab_glyph::{FontRef, PxScale};
+use chrono::DateTime;
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use imageproc::{drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut, text_size}, rect::Rect};
+
+use crate::fonts::{renderable_text, BOLD_FONT, FONT};
+
+const CHART_SIZE: (u32, u32) = (900, 500);
+
+// edges of the area data is drawn in
+const PLOT_LEFT: i32 = 100;
+const PLOT_RIGHT: i32 = CHART_SIZE.0 as i32 - 40;
+const PLOT_TOP: i32 = 70;
+const PLOT_BOTTOM: i32 = CHART_SIZE.1 as i32 - 80;
+
+/// Number of horizontal grid lines, not counting the x axis
+const Y_TICKS: i64 = 5;
+/// Number of dates labeled on the x axis of line charts
+const X_TICKS: i64 = 4;
+
+const BACKGROUND: Rgba<u8> = Rgba([30, 31, 34, 255]);
+/// b01lers red, same as embeds
+const ACCENT: Rgba<u8> = Rgba([0xc2, 0x20, 0x26, 255]);
+const GRID: Rgba<u8> = Rgba([64, 66, 73, 255]);
+const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const SECONDARY_TEXT: Rgba<u8> = Rgba([181, 186, 193, 255]);
+
+const TITLE_SCALE: f32 = 30.0;
+const LABEL_SCALE: f32 = 16.0;
+
+struct Chart {
+    image: RgbaImage,
+    font: FontRef<'static>,
+    /// Highest value on the y axis
+    y_max: i64,
+}
+
+impl Chart {
+    /// Creates a chart with a title and y axis grid lines, `y_max` is rounded up so the grid lines are at round numbers
+    fn new(title: &str, y_max: i64, format_y: impl Fn(i64) -> String) -> anyhow::Result<Self> {
+        let font = FontRef::try_from_slice(FONT)?;
+        let bold_font = FontRef::try_from_slice(BOLD_FONT)?;
+
+        let mut image = RgbaImage::from_pixel(CHART_SIZE.0, CHART_SIZE.1, BACKGROUND);
+        let title = renderable_text(&bold_font, title);
+        draw_text_mut(&mut image, TEXT, PLOT_LEFT, 20, PxScale::from(TITLE_SCALE), &bold_font, &title);
+
+        // divide rounding up, so the highest value is always on the chart
+        let y_step = nice_step((y_max.max(1) + Y_TICKS - 1) / Y_TICKS);
+        let y_max = y_step * Y_TICKS;
+
+        let mut chart = Chart { image, font, y_max };
+
+        for i in 0..=Y_TICKS {
+            let value = y_step * i;
+            let y = chart.y_to_pixel(value) as f32;
+
+            draw_line_segment_mut(&mut chart.image, (PLOT_LEFT as f32, y), (PLOT_RIGHT as f32, y), GRID);
+
+            let label = format_y(value);
+            let (width, height) = text_size(PxScale::from(LABEL_SCALE), &chart.font, &label);
+            chart.draw_label(&label, PLOT_LEFT - 10 - width as i32, y as i32 - height as i32 / 2);
+        }
+
+        Ok(chart)
+    }
+
+    fn y_to_pixel(&self, value: i64) -> i32 {
+        let plot_height = (PLOT_BOTTOM - PLOT_TOP) as f64;
+        PLOT_BOTTOM - (value as f64 / self.y_max as f64 * plot_height) as i32
+    }
+
+    fn draw_label(&mut self, text: &str, x: i32, y: i32) {
+        let text = renderable_text(&self.font, text);
+        draw_text_mut(&mut self.image, SECONDARY_TEXT, x, y, PxScale::from(LABEL_SCALE), &self.font, &text);
+    }
+
+    /// Draws a label centered on `x` below the plot
+    fn draw_x_label(&mut self, text: &str, x: i32) {
+        // measure the text that will be drawn, so labels with emojis are still centered
+        let text = renderable_text(&self.font, text);
+        let (width, _) = text_size(PxScale::from(LABEL_SCALE), &self.font, &text);
+        // keep labels at the edges inside the image
+        let left = (x - width as i32 / 2).clamp(10, CHART_SIZE.0 as i32 - width as i32 - 10);
+        self.draw_label(&text, left, PLOT_BOTTOM + 12);
+    }
+
+    fn png_bytes(self) -> anyhow::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        DynamicImage::ImageRgba8(self.image).write_to(&mut Cursor::new(&mut out), ImageFormat::Png)?;
+
+        Ok(out)
+    }
+}
+
+/// Rounds a grid step up to 1, 2, or 5 times a power of 10
+fn nice_step(step: i64) -> i64 {
+    let mut magnitude = 1;
+    while magnitude * 10 <= step {
+        magnitude *= 10;
+    }
+
+    [1, 2, 5, 10].into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|nice| *nice >= step)
+        .unwrap_or(10 * magnitude)
+}
+
+/// Shortens a label to `max_chars` characters so it fits under its bar
+fn truncate_label(label: &str, max_chars: usize) -> String {
+    if label.chars().count() <= max_chars {
+        label.to_string()
+    } else {
+        let mut label: String = label.chars().take(max_chars.saturating_sub(1)).collect();
+        label.push('…');
+        label
+    }
+}
+
+/// Draws a line through `(unix timestamp, value)` points, which must be sorted by time
+pub fn line_chart(title: &str, points: &[(i64, i64)], format_y: impl Fn(i64) -> String) -> anyhow::Result<Vec<u8>> {
+    let y_max = points.iter().map(|(_, y)| *y).max().unwrap_or_default();
+    let mut chart = Chart::new(title, y_max, format_y)?;
+
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        chart.draw_label("No data yet", PLOT_LEFT + 20, PLOT_TOP + 20);
+        return chart.png_bytes();
+    };
+
+    let start_time = first.0;
+    let duration = (last.0 - start_time).max(1);
+    let time_to_pixel = |time: i64| {
+        PLOT_LEFT + ((time - start_time) as f64 / duration as f64 * (PLOT_RIGHT - PLOT_LEFT) as f64) as i32
+    };
+
+    for i in 0..=X_TICKS {
+        let time = start_time + duration * i / X_TICKS;
+        let date = DateTime::from_timestamp(time, 0)
+            .map(|date| date.format("%b %d %Y").to_string())
+            .unwrap_or_default();
+
+        chart.draw_x_label(&date, time_to_pixel(time));
+    }
+
+    let pixels: Vec<(f32, f32)> = points.iter()
+        .map(|(time, value)| (time_to_pixel(*time) as f32, chart.y_to_pixel(*value) as f32))
+        .collect();
+
+    for segment in pixels.windows(2) {
+        // draw 3 lines next to each other so the line is easier to see
+        for offset in [-1.0, 0.0, 1.0] {
+            draw_line_segment_mut(
+                &mut chart.image,
+                (segment[0].0, segment[0].1 + offset),
+                (segment[1].0, segment[1].1 + offset),
+                ACCENT,
+            );
+        }
+    }
+
+    if let [(x, y)] = pixels[..] {
+        draw_filled_circle_mut(&mut chart.image, (x as i32, y as i32), 4, ACCENT);
+    }
+
+    chart.png_bytes()
+}
+
+/// Draws a bar for each `(label, value)`
+pub fn bar_chart(title: &str, bars: &[(String, i64)], format_y: impl Fn(i64) -> String) -> anyhow::Result<Vec<u8>> {
+    let y_max = bars.iter().map(|(_, y)| *y).max().unwrap_or_default();
+    let mut chart = Chart::new(title, y_max, format_y)?;
+
+    if bars.is_empty() {
+        chart.draw_label("No data yet", PLOT_LEFT + 20, PLOT_TOP + 20);
+        return chart.png_bytes();
+    }
+
+    let slot_width = (PLOT_RIGHT - PLOT_LEFT) / bars.len() as i32;
+    let bar_width = (slot_width * 3 / 4).max(1);
+    // roughly how many label characters fit in a slot
+    let max_label_chars = (slot_width / 9).max(1) as usize;
+
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let slot_left = PLOT_LEFT + slot_width * i as i32;
+        let bar_left = slot_left + (slot_width - bar_width) / 2;
+        let bar_top = chart.y_to_pixel(*value);
+
+        if bar_top < PLOT_BOTTOM {
+            let bar = Rect::at(bar_left, bar_top).of_size(bar_width as u32, (PLOT_BOTTOM - bar_top) as u32);
+            draw_filled_rect_mut(&mut chart.image, bar, ACCENT);
+        }
+
+        // remove characters the font can't draw before truncating, so they don't take up room
+        let label = renderable_text(&chart.font, label);
+        chart.draw_x_label(&truncate_label(&label, max_label_chars), slot_left + slot_width / 2);
+    }
+
+    chart.png_bytes()
+}
@@ -1,8 +1,15 @@
-use serenity::all::EditChannel;
+use poise::CreateReply;
+use serenity::all::{CreateAttachment, CreateEmbed, EditChannel, Mentionable};
+use strum::IntoEnumIterator;
 
+use crate::bingo::BingoCard;
 use crate::config::config;
 use crate::commands::{CmdContext, Error, has_perms};
-use crate::commands::competition::get_competition_from_ctx;
+use crate::commands::competition::{get_competition_from_ctx, scoreboard_file_name};
+use crate::db::{ChallengeType, Competition, CompetitionResult};
+
+/// Number of top contributors listed in the end of ctf summary
+const TOP_CONTRIBUTOR_COUNT: u32 = 5;
 
 /// Archives the current competition channel.
 #[poise::command(slash_command)]
@@ -37,8 +44,103 @@ pub async fn archive(ctx: CmdContext<'_>) -> Result<(), Error> {
         .edit(ctx, EditChannel::new().category(archived_category_id))
         .await?;
 
-    ctx.say(format!("Archived **{}**.", competition.name))
-        .await?;
+    send_summary(&ctx, &competition).await?;
+
+    Ok(())
+}
+
+/// Sends the end of ctf summary with placement, solves, top contributors, and the final bingo card
+async fn send_summary(ctx: &CmdContext<'_>, competition: &Competition) -> Result<(), Error> {
+    let mut conn = ctx.data().conn().await;
+
+    let result = conn.get_competition_result(competition.channel_id).await?;
+    let solved_challenges = conn.get_solved_challenges_for_competition(competition.channel_id).await?;
+    let top_solvers = conn.get_top_solvers_for_competition(competition.channel_id, TOP_CONTRIBUTOR_COUNT).await?;
+
+    let scoreboard = match &result {
+        Some(result) => fetch_scoreboard(ctx, result).await,
+        None => None,
+    };
+
+    let mut summary_embed = CreateEmbed::new()
+        .title(format!("Archived {}", competition.name))
+        .description(format!("Here is how b01lers did in {}", competition.channel_id.mention()))
+        .color(0xc22026)
+        .image("attachment://bingo_squares.png");
+
+    summary_embed = match &result {
+        Some(result) => {
+            let mut embed = summary_embed
+                .field("Placement", result.placement_string(), true)
+                .field("Points", result.points.to_string(), true);
+
+            if scoreboard.is_some() {
+                embed = embed.field("Scoreboard", "Screenshot attached", true);
+            }
+
+            embed
+        },
+        None => summary_embed.field("Placement", "Not recorded, use `/competition result`", false),
+    };
+
+    let mut category_counts = String::new();
+    for category in ChallengeType::iter() {
+        let solve_count = solved_challenges.iter()
+            .filter(|challenge| challenge.category == category)
+            .count();
+
+        if solve_count > 0 {
+            category_counts.push_str(&format!("{category}: {solve_count}\n"));
+        }
+    }
+
+    if category_counts.is_empty() {
+        category_counts.push_str("No challenges solved");
+    }
+
+    summary_embed = summary_embed.field(
+        format!("Challenges Solved ({})", solved_challenges.len()),
+        category_counts,
+        false,
+    );
+
+    let contributors = top_solvers
+        .iter()
+        .enumerate()
+        .map(|(i, solver)| format!("{}. {} ({} solves)", i + 1, solver.user_id.mention(), solver.solve_count))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if !contributors.is_empty() {
+        summary_embed = summary_embed.field("Top Contributors", contributors, false);
+    }
+
+    let bingo_attachment = CreateAttachment::bytes(
+        BingoCard::for_competition(competition)?.render_png_bytes()?,
+        "bingo_squares.png",
+    );
+
+    let mut reply = CreateReply::default()
+        .embed(summary_embed)
+        .attachment(bingo_attachment);
+
+    if let Some(scoreboard) = scoreboard {
+        reply = reply.attachment(scoreboard);
+    }
+
+    ctx.send(reply).await?;
 
     Ok(())
 }
+
+/// Downloads the scoreboard screenshot from the message it was posted with,
+/// `None` if there is no screenshot or the message was deleted
+async fn fetch_scoreboard(ctx: &CmdContext<'_>, result: &CompetitionResult) -> Option<CreateAttachment> {
+    let (channel_id, message_id) = result.scoreboard_message?;
+
+    let message = channel_id.message(ctx, message_id).await.ok()?;
+    let screenshot = message.attachments.first()?;
+    let bytes = screenshot.download().await.ok()?;
+
+    Some(CreateAttachment::bytes(bytes, scoreboard_file_name(&screenshot.filename)))
+}
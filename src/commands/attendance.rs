@@ -0,0 +1,99 @@
+use chacha20poly1305::aead::{OsRng, rand_core::RngCore};
+use poise::CreateReply;
+use serenity::all::{FormattedTimestamp, FormattedTimestampStyle};
+
+use crate::config::config;
+use crate::db::PointsReason;
+use crate::points::{give_points, points_to_string};
+
+use super::{CmdContext, Error, has_perms};
+
+/// Characters used in check in codes, leaving out ones which are easy to mix up like `0` and `O`
+const CODE_CHARACTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+#[poise::command(slash_command, subcommands("open", "checkin"))]
+pub async fn attendance(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+fn generate_code() -> String {
+    (0..CODE_LENGTH)
+        .map(|_| CODE_CHARACTERS[OsRng.next_u32() as usize % CODE_CHARACTERS.len()] as char)
+        .collect()
+}
+
+/// Opens check in for a meeting and gives you a code for members to check in with
+#[poise::command(slash_command)]
+pub async fn open(
+    ctx: CmdContext<'_>,
+    #[description = "Name of the meeting"] name: String,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to open meeting check in."));
+    }
+
+    let code = generate_code();
+
+    let mut conn = ctx.data().conn().await;
+    let meeting = conn.create_meeting(
+        &name,
+        &code,
+        ctx.author().id,
+        config().participation_points.attendance_code_minutes,
+    ).await?;
+    conn.commit().await?;
+
+    let reply = CreateReply::default()
+        .content(format!(
+            "Check in for {} is open until {}, share the code `{}` so members can use `/attendance checkin`.",
+            meeting.name,
+            FormattedTimestamp::new(meeting.closes_at, Some(FormattedTimestampStyle::ShortTime)),
+            meeting.code,
+        ))
+        .ephemeral(true);
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Checks in to a meeting to earn attendance points
+#[poise::command(slash_command)]
+pub async fn checkin(
+    ctx: CmdContext<'_>,
+    #[description = "Code given out at the meeting"] code: String,
+) -> Result<(), Error> {
+    let mut conn = ctx.data().conn().await;
+
+    let meeting = conn.get_open_meeting_by_code(&code.trim().to_uppercase()).await?
+        .ok_or_else(|| anyhow::anyhow!("That code is invalid or check in has closed."))?;
+
+    if !conn.add_meeting_attendee(meeting.id, ctx.author().id).await? {
+        return Err(anyhow::anyhow!("You have already checked in to {}.", meeting.name));
+    }
+
+    let points = config().participation_points.points_per_attendance.unwrap_or_default();
+    if points > 0 {
+        give_points(
+            ctx.serenity_context(),
+            &mut conn,
+            ctx.author().id,
+            points,
+            &PointsReason::Attendance { meeting_id: meeting.id },
+            None,
+        ).await?;
+    }
+
+    conn.commit().await?;
+
+    let content = if points > 0 {
+        format!("Checked in to {} and earned {} points.", meeting.name, points_to_string(points))
+    } else {
+        format!("Checked in to {}.", meeting.name)
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
@@ -1,30 +1,243 @@
+use std::collections::HashMap;
+
 use poise::CreateReply;
-use serenity::all::CreateAttachment;
+use serenity::all::{
+    AutocompleteChoice, ButtonStyle, ChannelId, ComponentInteraction, Context, CreateActionRow, CreateAttachment,
+    CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
+    FormattedTimestamp, FormattedTimestampStyle, Mentionable, UserId,
+};
 
-use crate::db::BingoSquare;
-use crate::commands::{CmdContext, Error};
+use crate::achievements::check_bingo_achievements;
+use crate::bingo::{board_name, normalize_label, BingoCard, BingoSquare};
+use crate::config::{config, MAX_BINGO_LABEL_LENGTH};
+use crate::db::{BingoChange, Competition, DbConn};
+use crate::commands::{CmdContext, CommandContext, Error, has_perms};
 use crate::commands::competition::get_competition_from_ctx;
+use crate::commands::pagination::paginate_embeds;
+
+/// Most suggestions discord shows for an autocompleted argument
+const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+/// Number of lines on one page of the bingo leaderboard, stats, and history
+const BINGO_PAGE_LENGTH: usize = 15;
+
+/// Prefix of the custom id of bingo square buttons, followed by `<competition id>:<square index>:<board name>`
+pub const SQUARE_BUTTON_PREFIX: &str = "bingo_square:";
+
+/// Discord allows at most 5 rows of 5 buttons on a message
+const MAX_BUTTON_GRID_SIZE: usize = 5;
+
+/// Longest label discord allows on a button
+const MAX_BUTTON_LABEL_LENGTH: usize = 80;
+
+fn bingo_attachment(card: &BingoCard) -> Result<CreateAttachment, Error> {
+    Ok(CreateAttachment::bytes(card.render_png_bytes()?, "bingo_squares.png"))
+}
 
-async fn send_bingo_image(ctx: &CmdContext<'_>, image: &[u8]) -> Result<(), Error> {
-    let attachment = CreateAttachment::bytes(image, "bingo_squares.png");
-    let reply = CreateReply::default().attachment(attachment);
+async fn send_bingo_image(ctx: &CmdContext<'_>, competition: &Competition) -> Result<(), Error> {
+    let card = BingoCard::for_competition(competition)?;
+    let reply = CreateReply::default().attachment(bingo_attachment(&card)?);
 
     ctx.send(reply).await?;
     Ok(())
 }
 
+/// Creates a button for every square on the board laid out like the board, which toggle the square when clicked
+///
+/// Boards too big to fit in discord's button limit get no buttons, and bonus squares never get buttons.
+fn square_buttons(card: &BingoCard, competition: &Competition) -> Vec<CreateActionRow> {
+    if card.board.size > MAX_BUTTON_GRID_SIZE {
+        return Vec::new();
+    }
+
+    let competition_id = competition.channel_id;
+    let board = board_name(competition);
+
+    card.board.squares.chunks(card.board.size)
+        .enumerate()
+        .map(|(row, labels)| {
+            let buttons = labels.iter()
+                .enumerate()
+                .map(|(column, label)| {
+                    let i = row * card.board.size + column;
+                    let square = BingoSquare::Board(i);
+
+                    let style = if card.is_marked(square) { ButtonStyle::Danger } else { ButtonStyle::Secondary };
+
+                    CreateButton::new(format!("{SQUARE_BUTTON_PREFIX}{competition_id}:{i}:{board}"))
+                        .label(label.chars().take(MAX_BUTTON_LABEL_LENGTH).collect::<String>())
+                        .style(style)
+                        // the free square can't be unmarked
+                        .disabled(card.is_free(square))
+                })
+                .collect();
+
+            CreateActionRow::Buttons(buttons)
+        })
+        .collect()
+}
+
+/// Suggests squares on the current competition's board which are marked or unmarked
+async fn autocomplete_squares(ctx: CmdContext<'_>, partial: &str, marked: bool) -> Vec<AutocompleteChoice> {
+    let Ok(competition) = get_competition_from_ctx(&ctx).await else {
+        return Vec::new();
+    };
+    let Ok(card) = BingoCard::for_competition(&competition) else {
+        return Vec::new();
+    };
+
+    let partial = normalize_label(partial);
+    card.squares(marked)
+        .filter(|(_, label)| normalize_label(label).contains(&partial))
+        .take(MAX_AUTOCOMPLETE_CHOICES)
+        .map(|(square, label)| match square {
+            BingoSquare::Board(_) => AutocompleteChoice::new(label, label),
+            // bonus squares are listed after the board, so tell them apart, cutting the name to discord's limit
+            BingoSquare::Bonus(_) => {
+                let name: String = format!("bonus: {label}").chars().take(MAX_BINGO_LABEL_LENGTH).collect();
+                AutocompleteChoice::new(name, label)
+            },
+        })
+        .collect()
+}
+
+async fn autocomplete_unmarked_squares(ctx: CmdContext<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    autocomplete_squares(ctx, partial, false).await
+}
+
+async fn autocomplete_marked_squares(ctx: CmdContext<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    autocomplete_squares(ctx, partial, true).await
+}
+
+async fn autocomplete_boards(_ctx: CmdContext<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    config().bingo.boards.keys()
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(MAX_AUTOCOMPLETE_CHOICES)
+        .cloned()
+        .collect()
+}
+
+/// Marks or unmarks the square with the label on the competition's board
+///
+/// # Returns
+///
+/// Returns the label of the square as it is written on the board
+fn set_square_marked(competition: &mut Competition, label: &str, marked: bool) -> Result<String, Error> {
+    let card = BingoCard::for_competition(competition)?;
+    let square = card.find_square(label)
+        .ok_or_else(|| anyhow::anyhow!("There is no bingo square called {label}."))?;
+    let label = card.label(square).to_string();
+
+    set_marked(competition, square, marked);
+
+    Ok(label)
+}
+
+fn set_marked(competition: &mut Competition, square: BingoSquare, marked: bool) {
+    let (marks, i) = match square {
+        BingoSquare::Board(i) => (&mut competition.bingo, i),
+        BingoSquare::Bonus(i) => (&mut competition.bingo_bonus, i),
+    };
+
+    if marked {
+        *marks |= 1 << i;
+    } else {
+        *marks &= !(1 << i);
+    }
+}
+
+/// Saves a square being marked or unmarked by the user, and awards any bingo achievements it completes
+///
+/// Achievements are announced after `conn` is committed.
+async fn save_square_change(
+    context: &Context,
+    conn: &mut DbConn<'_>,
+    competition: &Competition,
+    square: &str,
+    marked: bool,
+    user_id: UserId,
+) -> Result<(), Error> {
+    conn.create_bingo_event(competition.channel_id, board_name(competition), square, marked, user_id).await?;
+
+    if marked {
+        check_bingo_achievements(context, conn, competition).await?;
+    }
+
+    conn.update_competition(competition.clone()).await?;
+
+    Ok(())
+}
+
+/// Toggles the square of the clicked button, and redraws the board and buttons on the message
+pub async fn handle_square_button(context: &Context, cmd_context: &CommandContext, interaction: &ComponentInteraction) -> anyhow::Result<()> {
+    let (competition_id, i, board) = interaction.data.custom_id
+        .strip_prefix(SQUARE_BUTTON_PREFIX)
+        .and_then(|id| {
+            let mut parts = id.splitn(3, ':');
+            Some((parts.next()?.parse::<u64>().ok()?, parts.next()?.parse::<usize>().ok()?, parts.next()?))
+        })
+        .ok_or_else(|| anyhow::anyhow!("Invalid bingo square button"))?;
+
+    // acknowledge first, saving the square and drawing the board can take longer than 3 seconds
+    interaction.create_response(context, CreateInteractionResponse::Acknowledge).await?;
+
+    let mut conn = cmd_context.conn().await;
+    let mut competition = conn.get_competition(ChannelId::new(competition_id)).await?;
+
+    // the board could have been changed since the buttons were sent, so the same square index is a different square
+    if board != board_name(&competition) {
+        conn.commit().await?;
+
+        let followup = CreateInteractionResponseFollowup::new()
+            .content("This bingo board has been replaced, use `/bingo status` to see the new one.")
+            .ephemeral(true);
+        interaction.create_followup(context, followup).await?;
+
+        return Ok(());
+    }
+
+    let card = BingoCard::for_competition(&competition)?;
+    if i >= card.board.squares.len() {
+        return Err(anyhow::anyhow!("Bingo square {i} is not on the board of {}", competition.name));
+    }
+
+    let square = BingoSquare::Board(i);
+    let marked = !card.is_marked(square);
+    let label = card.label(square).to_string();
+
+    set_marked(&mut competition, square, marked);
+    save_square_change(context, &mut conn, &competition, &label, marked, interaction.user.id).await?;
+
+    conn.commit().await?;
+
+    let card = BingoCard::for_competition(&competition)?;
+    let response = EditInteractionResponse::new()
+        .new_attachment(bingo_attachment(&card)?)
+        .components(square_buttons(&card, &competition));
+
+    interaction.edit_response(context, response).await?;
+
+    Ok(())
+}
+
 // This can never be called, just needed for bingo subcommands
-#[poise::command(slash_command, subcommands("add", "status", "remove"))]
+#[poise::command(slash_command, subcommands("add", "status", "remove", "board", "leaderboard", "stats", "history"))]
 pub async fn bingo(_ctx: CmdContext<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// Displays the current status of the bad ctf bingo squares
+/// Displays the current status of the bad ctf bingo squares, with buttons to mark them
 #[poise::command(slash_command)]
 pub async fn status(ctx: CmdContext<'_>) -> Result<(), Error> {
     let competition = get_competition_from_ctx(&ctx).await?;
+    let card = BingoCard::for_competition(&competition)?;
+
+    let reply = CreateReply::default()
+        .attachment(bingo_attachment(&card)?)
+        .components(square_buttons(&card, &competition));
 
-    send_bingo_image(&ctx, &competition.get_bingo_picture_png_bytes()?).await?;
+    ctx.send(reply).await?;
 
     Ok(())
 }
@@ -33,18 +246,20 @@ pub async fn status(ctx: CmdContext<'_>) -> Result<(), Error> {
 #[poise::command(slash_command)]
 pub async fn add(
     ctx: CmdContext<'_>,
-    #[description = "The bingo square to check off"] square: BingoSquare,
+    #[description = "The bingo square to check off"]
+    #[autocomplete = "autocomplete_unmarked_squares"]
+    square: String,
 ) -> Result<(), Error> {
     let mut competition = get_competition_from_ctx(&ctx).await?;
-    competition.bingo |= square;
+    let square = set_square_marked(&mut competition, &square, true)?;
 
+    // save before drawing the board, so the database isn't locked while the image is sent
     let mut conn = ctx.data().conn().await;
-
-    send_bingo_image(&ctx, &competition.get_bingo_picture_png_bytes()?).await?;
-    conn.update_competition(competition).await?;
-
+    save_square_change(ctx.serenity_context(), &mut conn, &competition, &square, true, ctx.author().id).await?;
     conn.commit().await?;
 
+    send_bingo_image(&ctx, &competition).await?;
+
     Ok(())
 }
 
@@ -52,17 +267,200 @@ pub async fn add(
 #[poise::command(slash_command)]
 pub async fn remove(
     ctx: CmdContext<'_>,
-    #[description = "The bingo square to uncheck"] square: BingoSquare,
+    #[description = "The bingo square to uncheck"]
+    #[autocomplete = "autocomplete_marked_squares"]
+    square: String,
 ) -> Result<(), Error> {
     let mut competition = get_competition_from_ctx(&ctx).await?;
-    competition.bingo.remove(square);
+    let square = set_square_marked(&mut competition, &square, false)?;
 
     let mut conn = ctx.data().conn().await;
+    save_square_change(ctx.serenity_context(), &mut conn, &competition, &square, false, ctx.author().id).await?;
+    conn.commit().await?;
+
+    send_bingo_image(&ctx, &competition).await?;
+
+    Ok(())
+}
+
+/// Switches the current competition to a different bingo board, clearing its marked squares
+#[poise::command(slash_command)]
+pub async fn board(
+    ctx: CmdContext<'_>,
+    #[description = "Name of the bingo board from the bot config"]
+    #[autocomplete = "autocomplete_boards"]
+    name: String,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to change the bingo board."));
+    }
+
+    if !config().bingo.boards.contains_key(&name) {
+        return Err(anyhow::anyhow!("There is no bingo board called {name}."));
+    }
 
-    send_bingo_image(&ctx, &competition.get_bingo_picture_png_bytes()?).await?;
-    conn.update_competition(competition).await?;
+    let mut competition = get_competition_from_ctx(&ctx).await?;
+    competition.bingo = 0;
+    competition.bingo_bonus = 0;
 
+    let mut conn = ctx.data().conn().await;
+    conn.create_bingo_board_event(competition.channel_id, &name, ctx.author().id).await?;
+    competition.bingo_board = Some(name);
+    conn.update_competition(competition.clone()).await?;
     conn.commit().await?;
 
+    send_bingo_image(&ctx, &competition).await?;
+
+    Ok(())
+}
+
+fn bingo_page(title: &str, description: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .color(0xc22026)
+}
+
+/// Ranks past competitions by how many bingo lines they completed, then by how many squares they marked
+#[poise::command(slash_command)]
+pub async fn leaderboard(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let competitions = ctx.data().conn().await.get_all_competitions().await?;
+
+    // competitions whose board was removed from config can't be scored
+    let mut entries: Vec<(Competition, usize, usize)> = competitions.into_iter()
+        .filter_map(|competition| {
+            let card = BingoCard::for_competition(&competition).ok()?;
+            let lines = card.completed_lines().len();
+            let squares = card.squares(true).count();
+
+            Some((competition, lines, squares))
+        })
+        .filter(|(_, _, squares)| *squares > 0)
+        .collect();
+    entries.sort_by(|(_, a_lines, a_squares), (_, b_lines, b_squares)| (b_lines, b_squares).cmp(&(a_lines, a_squares)));
+
+    let mut pages = Vec::new();
+    for (page_number, page_entries) in entries.chunks(BINGO_PAGE_LENGTH).enumerate() {
+        let mut competitions = String::new();
+        let mut scores = String::new();
+
+        for (i, (competition, lines, squares)) in page_entries.iter().enumerate() {
+            let i = page_number * BINGO_PAGE_LENGTH + i;
+            competitions.push_str(&format!("{}. {}\n", i + 1, competition.channel_id.mention()));
+            scores.push_str(&format!("{lines} lines, {squares} squares\n"));
+        }
+
+        pages.push(bingo_page("Bad CTF Bingo Leaderboard", "The worst CTFs b01lers has played")
+            .field("Competitions", competitions, true)
+            .field("Bingo", scores, true));
+    }
+
+    if pages.is_empty() {
+        pages.push(bingo_page("Bad CTF Bingo Leaderboard", "No competitions have marked a bingo square yet"));
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+/// Shows how often each bingo square happens across every competition which played bingo
+#[poise::command(slash_command)]
+pub async fn stats(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let competitions = ctx.data().conn().await.get_all_competitions().await?;
+
+    // squares are counted by label so the same square on different boards is counted together
+    let mut square_counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for competition in competitions.iter() {
+        let Ok(card) = BingoCard::for_competition(competition) else {
+            continue;
+        };
+
+        // a competition where nothing was marked probably didn't play bingo at all
+        if card.squares(true).next().is_none() {
+            continue;
+        }
+
+        for marked in [true, false] {
+            for (_, label) in card.squares(marked) {
+                let (marked_count, played_count) = square_counts.entry(label.to_string()).or_default();
+                *played_count += 1;
+                if marked {
+                    *marked_count += 1;
+                }
+            }
+        }
+    }
+
+    let mut square_counts: Vec<(String, usize, usize)> = square_counts.into_iter()
+        .map(|(label, (marked_count, played_count))| (label, marked_count, played_count))
+        .collect();
+    square_counts.sort_by(|(a_label, a_marked, a_played), (b_label, b_marked, b_played)| {
+        // compare rates without dividing, a/b > c/d is a*d > c*b
+        (b_marked * a_played).cmp(&(a_marked * b_played))
+            .then(b_marked.cmp(a_marked))
+            .then(a_label.cmp(b_label))
+    });
+
+    let mut pages: Vec<CreateEmbed> = square_counts.chunks(BINGO_PAGE_LENGTH)
+        .map(|page_counts| {
+            let description = page_counts.iter()
+                .map(|(label, marked_count, played_count)| format!(
+                    "**{label}**: {marked_count} of {played_count} CTFs ({}%)",
+                    marked_count * 100 / played_count,
+                ))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            bingo_page("Bad CTF Bingo Stats", &description)
+        })
+        .collect();
+
+    if pages.is_empty() {
+        pages.push(bingo_page("Bad CTF Bingo Stats", "No competitions have marked a bingo square yet"));
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+/// Lists who marked and unmarked bingo squares in the current competition, and when
+#[poise::command(slash_command)]
+pub async fn history(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let competition = get_competition_from_ctx(&ctx).await?;
+    let events = ctx.data().conn().await.get_bingo_events_for_competition(competition.channel_id).await?;
+
+    // marks made on a previous board were cleared when the board changed
+    let current_board_start = events.iter()
+        .rposition(|event| matches!(event.change, BingoChange::Board))
+        .unwrap_or(0);
+    let lines: Vec<String> = events[current_board_start..].iter()
+        .map(|event| {
+            let change = match &event.change {
+                BingoChange::Square { square, marked: true } => format!("marked **{square}**"),
+                BingoChange::Square { square, marked: false } => format!("unmarked **{square}**"),
+                BingoChange::Board => format!("switched to the board **{}**", event.board),
+            };
+
+            format!(
+                "{} {} {change}",
+                FormattedTimestamp::new(event.created_at, Some(FormattedTimestampStyle::ShortDateTime)),
+                event.user_id.mention(),
+            )
+        })
+        .collect();
+
+    let title = format!("Bingo History for {}", competition.name);
+    let mut pages: Vec<CreateEmbed> = lines.chunks(BINGO_PAGE_LENGTH)
+        .map(|page_lines| bingo_page(&title, &page_lines.join("\n")))
+        .collect();
+
+    if pages.is_empty() {
+        pages.push(bingo_page(&title, "No bingo squares have been marked yet"));
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
     Ok(())
 }
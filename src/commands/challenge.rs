@@ -1,5 +1,5 @@
 use poise::CreateReply;
-use serenity::all::{CreateEmbed, CreateMessage};
+use serenity::all::{CreateEmbed, CreateMessage, Timestamp};
 use serenity::builder::CreateForumPost;
 
 use crate::commands::{CmdContext, Error};
@@ -44,6 +44,8 @@ pub async fn challenge(
         name: name.clone(),
         category,
         channel_id: Some(thread.id),
+        notes_message_id: None,
+        created_at: Timestamp::now(),
     };
     conn.create_challenge(challenge).await?;
 
@@ -1,15 +1,29 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
 use anyhow::Context;
-use serenity::all::{Builder, ChannelFlags, ChannelType, CreateChannel, CreateEmbed, CreateForumTag, CreateMessage, EditChannel, EditThread, ForumEmoji, ReactionType, ChannelId};
+use poise::CreateReply;
+use poise::macros::ChoiceParameter;
+use serenity::all::{Attachment, Builder, ChannelFlags, ChannelType, CreateAttachment, CreateChannel, CreateEmbed, CreateForumTag, CreateMessage, EditChannel, EditMessage, EditThread, ForumEmoji, GuildChannel, MessageId, ReactionType, ChannelId, Timestamp, UserId};
 use serenity::builder::CreateForumPost;
 
 use crate::config::config;
-use crate::db::{BingoSquare, Competition, Challenge};
+use crate::db::{Competition, CompetitionHistory, CompetitionResult, Challenge};
+use crate::semester::{Semester, Term};
 
 use super::{CmdContext, Error, has_perms};
+use super::uploads::{send_with_files, EMBED_DESCRIPTION_LIMIT};
+use super::credentials::{Credentials, reveal_button};
+
+// This can never be called, just needed for competition subcommands
+#[poise::command(slash_command, subcommands("create", "result", "history", "credentials", "export", "redact"))]
+pub async fn competition(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
 
 /// Creates a new ctf competition channel.
 #[poise::command(slash_command)]
-pub async fn competition(
+pub async fn create(
     ctx: CmdContext<'_>,
     #[description = "Name of the ctf"] name: String,
     #[description = "Url of ctf website"] url: String,
@@ -30,7 +44,7 @@ pub async fn competition(
 
     // TODO: prettier error
     // Create forum channel
-    let creds_str = &format!("**{name}**\n{url}\n\n**Username**: {username}\n**Password**: {password}");
+    let creds_str = &forum_topic(&format!("**{name}**\n{url}"));
     let mut forum = CreateChannel::new(&name)
         .category(config().server.ctf_category_id)
         .position(0)
@@ -62,14 +76,11 @@ pub async fn competition(
     forum.edit(ctx, EditChannel::new().available_tags(tags)).await?;
 
     // Create post with credentials
-    let credentials_embed = CreateEmbed::new()
-        .color(0xc22026)
-        .title(&format!("{name} credentials"))
-        .description(url)
-        .field("Username", username, false)
-        .field("Password", password, false);
+    let credentials_message = CreateMessage::new()
+        .add_embed(credentials_embed(&name, &url))
+        .components(reveal_button(forum.id));
 
-    let mut creds_channel = forum.create_forum_post(ctx, CreateForumPost::new("Credentials + general discussion", CreateMessage::new().add_embed(credentials_embed)))
+    let mut creds_channel = forum.create_forum_post(ctx, CreateForumPost::new(CREDENTIALS_POST_NAME, credentials_message))
         .await?;
 
     // Pin credentials / general discussion post
@@ -88,10 +99,16 @@ pub async fn competition(
     let competition = Competition {
         channel_id: forum.id,
         name: name.clone(),
-        bingo: BingoSquare::Free.into(),
+        bingo: 0,
+        created_at: Timestamp::now(),
+        bingo_board: None,
+        bingo_bonus: 0,
     };
     conn.create_competition(competition).await?;
 
+    let credentials = Credentials { username, password };
+    conn.set_competition_credentials(forum.id, &credentials.encrypt(&ctx.data().credentials_cipher, forum.id)?).await?;
+
     conn.commit().await?;
 
     ctx.say(format!("Created channel for **{name}**: {forum}"))
@@ -100,6 +117,455 @@ pub async fn competition(
     Ok(())
 }
 
+/// Name of the forum post the credentials embed is sent in
+const CREDENTIALS_POST_NAME: &str = "Credentials + general discussion";
+
+/// Topic of a competition forum, `header` is the competition name and url
+fn forum_topic(header: &str) -> String {
+    format!("{header}\n\nPress **Reveal credentials** in the credentials post to get the team login")
+}
+
+fn credentials_embed(name: &str, url: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .color(0xc22026)
+        .title(format!("{name} credentials"))
+        .description(format!("{url}\n\nVerified members can press the button below to see the team login."))
+}
+
+/// Changes the team login for the current competition.
+#[poise::command(slash_command)]
+pub async fn credentials(
+    ctx: CmdContext<'_>,
+    #[description = "Team username"] username: String,
+    #[description = "Team password or login url"] password: String,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!(
+            "You do not have permissions to change competition credentials."
+        ));
+    }
+
+    let competition = get_competition_from_ctx(&ctx).await?;
+
+    let credentials = Credentials { username, password };
+
+    let mut conn = ctx.data().conn().await;
+    conn.set_competition_credentials(competition.channel_id, &credentials.encrypt(&ctx.data().credentials_cipher, competition.channel_id)?).await?;
+    conn.commit().await?;
+
+    let reply = CreateReply::default()
+        .content(format!("Updated credentials for **{}**", competition.name))
+        .ephemeral(true);
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Records how b01lers placed in the current competition.
+#[poise::command(slash_command)]
+pub async fn result(
+    ctx: CmdContext<'_>,
+    #[description = "Place b01lers finished in"]
+    #[min = 1]
+    place: i64,
+    #[description = "Number of teams on the scoreboard"]
+    #[min = 1]
+    teams: i64,
+    #[description = "Points b01lers finished with"] points: i64,
+    #[description = "Screenshot of the final scoreboard"] scoreboard: Option<Attachment>,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!(
+            "You do not have permissions to record a competition result."
+        ));
+    }
+
+    if place > teams {
+        return Err(anyhow::anyhow!("Place can not be more than the number of teams."));
+    }
+
+    let competition = get_competition_from_ctx(&ctx).await?;
+
+    // Defer response because downloading the screenshot may take longer than 3 seconds
+    ctx.defer().await?;
+
+    let mut result = CompetitionResult {
+        competition_id: competition.channel_id,
+        place,
+        team_count: teams,
+        points,
+        scoreboard_message: None,
+    };
+
+    let mut result_embed = CreateEmbed::new()
+        .color(0xc22026)
+        .title(format!("{} result", competition.name))
+        .field("Placement", result.placement_string(), true)
+        .field("Points", result.points.to_string(), true);
+
+    // attachment urls expire, so the screenshot is uploaded again with the result
+    // and the message is stored to find it later
+    let mut reply = CreateReply::default();
+    if let Some(scoreboard) = &scoreboard {
+        let file_name = scoreboard_file_name(&scoreboard.filename);
+        result_embed = result_embed.image(format!("attachment://{file_name}"));
+        reply = reply.attachment(CreateAttachment::bytes(scoreboard.download().await?, file_name));
+    }
+
+    let reply_handle = ctx.send(reply.embed(result_embed)).await?;
+    let message = reply_handle.message().await?;
+    if scoreboard.is_some() {
+        result.scoreboard_message = Some((message.channel_id, message.id));
+    }
+
+    let mut conn = ctx.data().conn().await;
+    conn.set_competition_result(result).await?;
+    conn.commit().await?;
+
+    Ok(())
+}
+
+/// Name the scoreboard screenshot is uploaded with, keeping the extension of the original file
+pub fn scoreboard_file_name(original_name: &str) -> String {
+    let extension = Path::new(original_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png");
+
+    format!("scoreboard.{extension}")
+}
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum HistoryFormat {
+    #[name = "embed"]
+    Embed,
+    #[name = "csv"]
+    Csv,
+    #[name = "markdown"]
+    Markdown,
+}
+
+
+/// Lists past competitions b01lers has played.
+#[poise::command(slash_command)]
+pub async fn history(
+    ctx: CmdContext<'_>,
+    #[description = "Only list competitions from this semester"] semester: Option<Term>,
+    #[description = "Only list competitions from this year"] year: Option<i32>,
+    #[description = "Format to list competitions in, defaults to embed"] format: Option<HistoryFormat>,
+) -> Result<(), Error> {
+    let history = ctx.data().conn().await.get_competition_history().await?;
+
+    let history = history
+        .into_iter()
+        .filter(|entry| {
+            let played = Semester::from_timestamp(entry.created_at);
+
+            semester.is_none_or(|term| played.term == term) && year.is_none_or(|year| played.year == year)
+        })
+        .collect::<Vec<_>>();
+
+    let reply = match format.unwrap_or(HistoryFormat::Embed) {
+        HistoryFormat::Embed => {
+            let mut description = String::new();
+
+            for (i, entry) in history.iter().enumerate() {
+                let line = format!(
+                    "**{}** ({}): {}, {} solves, {} participants\n",
+                    entry.name,
+                    history_date(entry),
+                    history_placement(entry),
+                    entry.solve_count,
+                    entry.participant_count,
+                );
+
+                if description.len() + line.len() > EMBED_DESCRIPTION_LIMIT - 64 {
+                    description.push_str(&format!("...and {} more, export as csv or markdown to see all", history.len() - i));
+                    break;
+                }
+
+                description.push_str(&line);
+            }
+
+            if description.is_empty() {
+                description.push_str("No competitions played");
+            }
+
+            let history_embed = CreateEmbed::new()
+                .title("Competition History")
+                .description(description)
+                .color(0xc22026);
+
+            CreateReply::default().embed(history_embed)
+        },
+        HistoryFormat::Csv => {
+            let mut csv = String::from("name,date,semester,place,teams,points,solves,participants\n");
+
+            for entry in history.iter() {
+                let (place, teams, points) = match &entry.result {
+                    Some(result) => (result.place.to_string(), result.team_count.to_string(), result.points.to_string()),
+                    None => Default::default(),
+                };
+
+                csv.push_str(&format!(
+                    "{},{},{},{place},{teams},{points},{},{}\n",
+                    csv_escape(&entry.name),
+                    history_date(entry),
+                    Semester::from_timestamp(entry.created_at),
+                    entry.solve_count,
+                    entry.participant_count,
+                ));
+            }
+
+            CreateReply::default().attachment(CreateAttachment::bytes(csv, "competition_history.csv"))
+        },
+        HistoryFormat::Markdown => {
+            let mut markdown = String::from("# Competition History\n\n| Name | Date | Semester | Placement | Points | Solves | Participants |\n|---|---|---|---|---|---|---|\n");
+
+            for entry in history.iter() {
+                let points = entry.result.as_ref()
+                    .map(|result| result.points.to_string())
+                    .unwrap_or_default();
+
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {} | {points} | {} | {} |\n",
+                    entry.name.replace('|', "\\|"),
+                    history_date(entry),
+                    Semester::from_timestamp(entry.created_at),
+                    history_placement(entry),
+                    entry.solve_count,
+                    entry.participant_count,
+                ));
+            }
+
+            CreateReply::default().attachment(CreateAttachment::bytes(markdown, "competition_history.md"))
+        },
+    };
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Exports the current competition's challenges, writeups, and notes as markdown.
+#[poise::command(slash_command)]
+pub async fn export(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let competition = get_competition_from_ctx(&ctx).await?;
+
+    // Defer response because looking up every author may take longer than 3 seconds
+    ctx.defer().await?;
+
+    let mut conn = ctx.data().conn().await;
+
+    let challenges = conn.get_challenges_for_competition(competition.channel_id).await?;
+    let solved_ids = conn.get_solved_challenges_for_competition(competition.channel_id).await?
+        .iter()
+        .map(|challenge| challenge.id)
+        .collect::<HashSet<_>>();
+    let writeups = conn.get_writeups(Some(competition.channel_id), None, None).await?;
+
+    let mut author_names = HashMap::new();
+    let mut files = Vec::new();
+
+    let mut markdown = format!("# {}\n\n", competition.name);
+    markdown.push_str(&format!("Played {}\n\n", competition.created_at.format("%Y-%m-%d")));
+
+    if let Some(result) = conn.get_competition_result(competition.channel_id).await? {
+        markdown.push_str(&format!("Placed {} with {} points\n\n", result.placement_string(), result.points));
+    }
+
+    for challenge in challenges.iter() {
+        let status = if solved_ids.contains(&challenge.id) { "solved" } else { "unsolved" };
+        markdown.push_str(&format!("## {}/{} ({status})\n\n", challenge.category, challenge.name));
+
+        let challenge_writeups = writeups.iter()
+            .filter(|writeup| writeup.challenge_id == challenge.id)
+            .collect::<Vec<_>>();
+
+        if !challenge_writeups.is_empty() {
+            markdown.push_str("### Writeups\n\n");
+
+            for writeup in challenge_writeups {
+                let author = author_name(&ctx, &mut author_names, writeup.user_id).await;
+                markdown.push_str(&format!("- {} by {author}\n", writeup.url));
+            }
+
+            markdown.push('\n');
+        }
+
+        let notes = conn.get_notes_for_challenge(challenge.id).await?;
+
+        if !notes.is_empty() {
+            markdown.push_str("### Notes\n\n");
+
+            for note in notes {
+                let author = author_name(&ctx, &mut author_names, note.user_id).await;
+                markdown.push_str(&format!("- {} ({author})", note.content));
+
+                if let Some(file) = note.file {
+                    markdown.push_str(&format!(" `{}`", file.name));
+                    files.push(file);
+                }
+
+                markdown.push('\n');
+            }
+
+            markdown.push('\n');
+        }
+    }
+
+    let mut attachments = vec![CreateAttachment::bytes(markdown, format!("{}.md", competition.name))];
+    attachments.extend(files.into_iter().map(|file| CreateAttachment::bytes(file.data, file.name)));
+
+    send_with_files(&ctx, CreateReply::default(), attachments).await?;
+
+    Ok(())
+}
+
+/// Gets the name of a user, caching names which have already been looked up
+async fn author_name(ctx: &CmdContext<'_>, names: &mut HashMap<UserId, String>, user_id: UserId) -> String {
+    if let Some(name) = names.get(&user_id) {
+        return name.clone();
+    }
+
+    let name = match user_id.to_user(ctx).await {
+        Ok(user) => user.name,
+        Err(_) => user_id.to_string(),
+    };
+
+    names.insert(user_id, name.clone());
+    name
+}
+
+/// Date the competition was created
+fn history_date(entry: &CompetitionHistory) -> String {
+    entry.created_at.format("%Y-%m-%d").to_string()
+}
+
+fn history_placement(entry: &CompetitionHistory) -> String {
+    match &entry.result {
+        Some(result) => result.placement_string(),
+        None => "unknown placement".to_string(),
+    }
+}
+
+/// Quotes a csv field if it contains any special characters
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Moves team logins posted in plain text by older versions of the bot behind the reveal button.
+#[poise::command(slash_command)]
+pub async fn redact(ctx: CmdContext<'_>) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!(
+            "You do not have permissions to redact competition credentials."
+        ));
+    }
+
+    // Defer response because every competition channel has to be edited
+    ctx.defer().await?;
+
+    let competitions = ctx.data().conn().await.get_competitions_without_credentials().await?;
+
+    let mut redacted = Vec::new();
+    let mut failed = Vec::new();
+    for competition in competitions.iter() {
+        match redact_competition(&ctx, competition).await {
+            Ok(true) => redacted.push(format!("**{}**", competition.name)),
+            Ok(false) => (),
+            Err(e) => failed.push(format!("**{}**: {e}", competition.name)),
+        }
+    }
+
+    let mut response = if redacted.is_empty() {
+        String::from("No plain text credentials were found.")
+    } else {
+        format!("Moved credentials behind the reveal button for {}.", redacted.join(", "))
+    };
+
+    if !failed.is_empty() {
+        response.push_str(&format!("\nCould not redact:\n{}", failed.join("\n")));
+    }
+
+    ctx.say(response).await?;
+
+    Ok(())
+}
+
+/// Stores the plain text credentials of the competition encrypted, then removes them from the forum topic and credentials post
+///
+/// # Returns
+///
+/// Returns false if the competition has no plain text credentials
+async fn redact_competition(ctx: &CmdContext<'_>, competition: &Competition) -> Result<bool, Error> {
+    let forum = competition.channel_id.to_channel(ctx).await?
+        .guild()
+        .ok_or_else(|| anyhow::anyhow!("Competition channel is not in the server"))?;
+
+    let topic = forum.topic.as_deref().and_then(Credentials::parse_legacy_topic);
+    let post = find_credentials_post(ctx, &forum).await?;
+    let mut post_message = match &post {
+        // the first message of a forum post has the same id as the post
+        Some(post) => post.message(ctx, MessageId::new(post.id.get())).await.ok(),
+        None => None,
+    };
+    let embed_credentials = post_message.as_ref()
+        .and_then(|message| message.embeds.first())
+        .and_then(Credentials::parse_legacy_embed);
+
+    let credentials = match (&topic, &embed_credentials) {
+        (Some((_, credentials)), _) => credentials,
+        (None, Some((_, credentials))) => credentials,
+        (None, None) => return Ok(false),
+    };
+
+    // store the credentials first so they are never lost if editing the channel fails
+    let mut conn = ctx.data().conn().await;
+    conn.set_competition_credentials(forum.id, &credentials.encrypt(&ctx.data().credentials_cipher, forum.id)?).await?;
+    conn.commit().await?;
+
+    if let Some((header, _)) = &topic {
+        forum.id.edit(ctx, EditChannel::new().topic(forum_topic(header))).await?;
+    }
+
+    if let (Some(mut post), Some(message), Some((url, _))) = (post, post_message.as_mut(), &embed_credentials) {
+        // messages in archived posts can't be edited
+        let was_archived = post.thread_metadata.is_some_and(|metadata| metadata.archived);
+        if was_archived {
+            post.edit_thread(ctx, EditThread::new().archived(false)).await?;
+        }
+
+        message.edit(ctx, EditMessage::new()
+            .embed(credentials_embed(&competition.name, url))
+            .components(reveal_button(forum.id))
+        ).await?;
+
+        if was_archived {
+            post.edit_thread(ctx, EditThread::new().archived(true)).await?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Finds the credentials post in a competition forum, checking open posts and the latest 100 archived posts
+async fn find_credentials_post(ctx: &CmdContext<'_>, forum: &GuildChannel) -> Result<Option<GuildChannel>, Error> {
+    let active_threads = config().server.guild_id.get_active_threads(ctx).await?.threads;
+    let archived_threads = forum.id.get_archived_public_threads(ctx, None, Some(100)).await?.threads;
+
+    let post = active_threads.into_iter()
+        .chain(archived_threads)
+        .find(|thread| thread.parent_id == Some(forum.id) && thread.name == CREDENTIALS_POST_NAME);
+
+    Ok(post)
+}
+
 pub async fn get_competition_id_from_ctx(ctx: &CmdContext<'_>) -> Result<ChannelId, Error> {
     let Some(thread_channel) = ctx.guild_channel().await else {
         Err(anyhow::anyhow!("You are not inside a competition channel."))?
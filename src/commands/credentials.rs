@@ -0,0 +1,131 @@
+//! Stores competition credentials encrypted in the database
+//!
+//! Credentials are stored as an XChaCha20Poly1305 encrypted json with the nonce appended,
+//! using the key from the `CREDENTIALS_KEY` environment variable.
+//! The competition id is used as associated data, so credentials copied to another competition fail to decrypt.
+//! They are only shown to verified members through an ephemeral message when they press the reveal button.
+
+use chacha20poly1305::{AeadCore, XChaCha20Poly1305, aead::{OsRng, Aead, Payload}};
+use serde::{Serialize, Deserialize};
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Embed};
+
+use super::{CommandContext, user_has_role};
+use crate::config::config;
+
+const NONCE_SIZE: usize = 24;
+
+/// Prefix of the custom id of reveal credentials buttons, followed by the competition channel id
+pub const REVEAL_BUTTON_PREFIX: &str = "reveal_credentials:";
+
+#[derive(Serialize, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    pub fn encrypt(&self, cipher: &XChaCha20Poly1305, competition_id: ChannelId) -> anyhow::Result<Vec<u8>> {
+        let credentials_json = serde_json::to_string(self)?;
+        let aad = competition_id.get().to_be_bytes();
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut credentials = cipher.encrypt(&nonce, Payload { msg: credentials_json.as_bytes(), aad: &aad })
+            .or(Err(anyhow::anyhow!("Could not encrypt credentials")))?;
+
+        credentials.extend(nonce);
+
+        Ok(credentials)
+    }
+
+    pub fn decrypt(cipher: &XChaCha20Poly1305, competition_id: ChannelId, credentials: &[u8]) -> anyhow::Result<Self> {
+        if credentials.len() < NONCE_SIZE {
+            return Err(anyhow::anyhow!("Invalid encrypted credentials"));
+        }
+
+        // nonce is last 24 bytes of encrypted credentials
+        let nonce = &credentials[credentials.len() - NONCE_SIZE..];
+        let nonce: [u8; NONCE_SIZE] = nonce.try_into().unwrap();
+
+        let ciphertext = &credentials[..credentials.len() - NONCE_SIZE];
+        let aad = competition_id.get().to_be_bytes();
+
+        let credentials_json = cipher.decrypt(&nonce.into(), Payload { msg: ciphertext, aad: &aad })
+            .or(Err(anyhow::anyhow!("Could not decrypt credentials")))?;
+
+        Ok(serde_json::from_slice(&credentials_json)?)
+    }
+
+    /// Reads the credentials from a forum topic written before credentials were stored encrypted
+    ///
+    /// # Returns
+    ///
+    /// Returns the topic before the credentials, and the credentials
+    pub fn parse_legacy_topic(topic: &str) -> Option<(String, Credentials)> {
+        let (header, credentials) = topic.split_once("\n\n**Username**: ")?;
+        let (username, password) = credentials.split_once("\n**Password**: ")?;
+
+        let credentials = Credentials { username: username.to_string(), password: password.to_string() };
+        Some((header.to_string(), credentials))
+    }
+
+    /// Reads the credentials from a credentials post embed sent before credentials were stored encrypted
+    ///
+    /// # Returns
+    ///
+    /// Returns the competition url from the embed, and the credentials
+    pub fn parse_legacy_embed(embed: &Embed) -> Option<(String, Credentials)> {
+        let field = |name: &str| embed.fields.iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value.clone());
+
+        let credentials = Credentials { username: field("Username")?, password: field("Password")? };
+        Some((embed.description.clone().unwrap_or_default(), credentials))
+    }
+}
+
+/// Creates the button members use to reveal the credentials of a competition
+pub fn reveal_button(competition_id: ChannelId) -> Vec<CreateActionRow> {
+    let button = CreateButton::new(format!("{REVEAL_BUTTON_PREFIX}{competition_id}"))
+        .label("Reveal credentials")
+        .emoji('🔑')
+        .style(ButtonStyle::Primary);
+
+    vec![CreateActionRow::Buttons(vec![button])]
+}
+
+/// Sends the competition credentials as an ephemeral message if the user is a verified member
+pub async fn handle_reveal_button(context: &Context, cmd_context: &CommandContext, interaction: &ComponentInteraction) -> anyhow::Result<()> {
+    let competition_id = interaction.data.custom_id
+        .strip_prefix(REVEAL_BUTTON_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+        .ok_or_else(|| anyhow::anyhow!("Invalid reveal credentials button"))?;
+
+    let mut conn = cmd_context.conn().await;
+
+    let is_verified = match conn.get_user_by_id(interaction.user.id).await {
+        Ok(user) => user.is_verified(),
+        Err(_) => false,
+    };
+
+    let is_member = user_has_role(context, &interaction.user, &config().server.member_role).await?;
+
+    let response = if is_verified && is_member {
+        let credentials = conn.get_competition_credentials(ChannelId::new(competition_id)).await?;
+        let credentials = Credentials::decrypt(&cmd_context.credentials_cipher, ChannelId::new(competition_id), &credentials)?;
+
+        let credentials_embed = CreateEmbed::new()
+            .color(0xc22026)
+            .title("Credentials")
+            .field("Username", credentials.username, false)
+            .field("Password", credentials.password, false);
+
+        CreateInteractionResponseMessage::new().embed(credentials_embed)
+    } else {
+        CreateInteractionResponseMessage::new()
+            .content("Only verified members can view credentials, use `/verify email` to verify yourself")
+    };
+
+    interaction.create_response(context, CreateInteractionResponse::Message(response.ephemeral(true))).await?;
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use chacha20poly1305::{KeyInit, XChaCha20Poly1305, aead::OsRng};
 use serenity::all::{Member, Role, RoleId, UserId, Context, User};
 use tracing::info;
 
-use crate::{config::config, db::{DbConn, DbContext}, email::EmailClient};
+use crate::{config::config, db::{DbConn, DbContext}, email::EmailClient, message_points::{MessageThrottle, PendingMessagePoints}};
 
 pub mod competition;
 pub mod bingo;
@@ -14,20 +15,44 @@ pub mod verify;
 pub mod challenge;
 pub mod stats;
 pub mod misc;
+pub mod writeup;
+pub mod credentials;
+pub mod note;
+pub mod points;
+pub mod ranks;
+pub mod season;
+pub mod attendance;
+pub mod multiplier;
+pub mod pagination;
+pub mod uploads;
 
 pub struct CommandContext {
     pub db: DbContext,
     verify_token_cipher: XChaCha20Poly1305,
+    /// Cipher for competition credentials, uses a persistent key so they can be decrypted after restarts
+    credentials_cipher: XChaCha20Poly1305,
     email_client: EmailClient,
+    pub message_throttle: Arc<Mutex<MessageThrottle>>,
+    pub pending_message_points: Arc<Mutex<PendingMessagePoints>>,
 }
 
 impl CommandContext {
-    pub fn new(db: DbContext, email_client: EmailClient) -> Self {
+    pub fn new(
+        db: DbContext,
+        email_client: EmailClient,
+        credentials_key: &[u8],
+        message_throttle: Arc<Mutex<MessageThrottle>>,
+        pending_message_points: Arc<Mutex<PendingMessagePoints>>,
+    ) -> Self {
         let key = XChaCha20Poly1305::generate_key(&mut OsRng);
         CommandContext {
             db,
             verify_token_cipher: XChaCha20Poly1305::new(&key),
+            credentials_cipher: XChaCha20Poly1305::new_from_slice(credentials_key)
+                .expect("Credentials key must be 32 bytes"),
             email_client,
+            message_throttle,
+            pending_message_points,
         }
     }
 
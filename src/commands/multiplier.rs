@@ -0,0 +1,120 @@
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateMessage, FormattedTimestamp, FormattedTimestampStyle, Mentionable};
+
+use crate::config::config;
+
+use super::{CmdContext, Error, has_perms};
+
+#[poise::command(slash_command, subcommands("start", "end", "list"))]
+pub async fn multiplier(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Reloads the active multipliers so new messages use them
+async fn reload_multipliers(ctx: &CmdContext<'_>) -> anyhow::Result<()> {
+    let multipliers = ctx.data().conn().await.get_active_point_multipliers().await?;
+    ctx.data().message_throttle.lock().unwrap().set_multipliers(multipliers);
+
+    Ok(())
+}
+
+/// Multiplies message points for a while, like a double points weekend
+#[poise::command(slash_command)]
+pub async fn start(
+    ctx: CmdContext<'_>,
+    #[description = "Name of the event, like `double points weekend`"] name: String,
+    #[description = "Number to multiply message points by"]
+    #[min = 0.1]
+    #[max = 10.0]
+    multiplier: f64,
+    #[description = "Hours until the multiplier ends"]
+    #[min = 1]
+    hours: i64,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to start point multipliers."));
+    }
+
+    let mut conn = ctx.data().conn().await;
+    let point_multiplier = conn.create_point_multiplier(&name, multiplier, ctx.author().id, hours * 60).await?;
+    conn.commit().await?;
+
+    reload_multipliers(&ctx).await?;
+
+    let change_message = format!(
+        "{} started {} with {}x message points until {}",
+        ctx.author().id.mention(),
+        point_multiplier.name,
+        point_multiplier.multiplier,
+        FormattedTimestamp::new(point_multiplier.expires_at, Some(FormattedTimestampStyle::LongDateTime)),
+    );
+
+    let log_embed = CreateEmbed::new()
+        .title("Point Multiplier Started")
+        .description(&change_message)
+        .color(0xc22026);
+
+    config().server.bot_log_channel
+        .send_message(ctx, CreateMessage::new().add_embed(log_embed))
+        .await?;
+
+    ctx.say(format!("{change_message}.")).await?;
+
+    Ok(())
+}
+
+/// Ends a point multiplier early
+#[poise::command(slash_command)]
+pub async fn end(
+    ctx: CmdContext<'_>,
+    #[description = "Id of the multiplier, shown by `/multiplier list`"] id: i64,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to end point multipliers."));
+    }
+
+    let mut conn = ctx.data().conn().await;
+    if !conn.end_point_multiplier(id).await? {
+        return Err(anyhow::anyhow!("There is no running multiplier #{id}, use `/multiplier list` to see running multipliers."));
+    }
+    conn.commit().await?;
+
+    reload_multipliers(&ctx).await?;
+
+    ctx.say(format!("Ended multiplier #{id}.")).await?;
+
+    Ok(())
+}
+
+/// Lists the point multipliers which are running right now
+#[poise::command(slash_command)]
+pub async fn list(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let multipliers = ctx.data().conn().await.get_active_point_multipliers().await?;
+
+    let mut description = String::new();
+    for multiplier in multipliers.iter() {
+        description.push_str(&format!(
+            "`#{}` **{}** {}x until {}, started by {}\n",
+            multiplier.id,
+            multiplier.name,
+            multiplier.multiplier,
+            FormattedTimestamp::new(multiplier.expires_at, Some(FormattedTimestampStyle::ShortDateTime)),
+            multiplier.created_by.mention(),
+        ));
+    }
+
+    if description.is_empty() {
+        description.push_str("No multipliers are running");
+    } else {
+        description.push_str("\nOnly the highest multiplier applies when several are running.");
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Point Multipliers")
+        .description(description)
+        .color(0xc22026);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use poise::CreateReply;
+use serenity::all::{Attachment, CreateAttachment, CreateEmbed, CreateMessage, EditMessage, Mentionable};
+
+use crate::db::{Challenge, DbConn, Note, NoteFile};
+
+use super::{CmdContext, Error, competition::get_challenge_from_ctx};
+use super::uploads::{send_with_files, EMBED_DESCRIPTION_LIMIT};
+
+/// Max size of a file attached to a note
+const MAX_NOTE_FILE_SIZE: u32 = 8 * 1024 * 1024;
+
+#[poise::command(slash_command, subcommands("add", "list", "digest"))]
+pub async fn note(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Saves a note for the current channel's challenge
+#[poise::command(slash_command)]
+pub async fn add(
+    ctx: CmdContext<'_>,
+    #[description = "Contents of the note"] content: String,
+    #[description = "File to save with the note, like a script or decompiled code"] file: Option<Attachment>,
+) -> Result<(), Error> {
+    let challenge = get_challenge_from_ctx(&ctx).await?;
+
+    // defer because downloading the file may take longer than 3 seconds
+    ctx.defer().await?;
+
+    let file = match file {
+        Some(attachment) => {
+            if attachment.size > MAX_NOTE_FILE_SIZE {
+                return Err(anyhow::anyhow!("Note files can be at most 8 MiB"));
+            }
+
+            Some(NoteFile {
+                data: attachment.download().await?,
+                name: attachment.filename,
+            })
+        },
+        None => None,
+    };
+
+    let note = Note {
+        id: 0,
+        challenge_id: challenge.id,
+        user_id: ctx.author().id,
+        content,
+        file,
+    };
+
+    let mut conn = ctx.data().conn().await;
+    let note_id = conn.create_note(note).await?;
+
+    // keep pinned digest up to date
+    if let (Some(channel_id), Some(message_id)) = (challenge.channel_id, challenge.notes_message_id) {
+        let notes = conn.get_notes_for_challenge(challenge.id).await?;
+
+        let edit = EditMessage::new().embed(notes_embed(&challenge, &notes));
+        if channel_id.edit_message(ctx, message_id, edit).await.is_err() {
+            // the digest was deleted, so pin a new one
+            pin_digest(&ctx, &mut conn, &challenge, &notes).await?;
+        }
+    }
+
+    conn.commit().await?;
+
+    ctx.say(format!("Saved note #{note_id} for {}.", challenge.name)).await?;
+
+    Ok(())
+}
+
+/// Lists the notes for the current channel's challenge
+#[poise::command(slash_command)]
+pub async fn list(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let challenge = get_challenge_from_ctx(&ctx).await?;
+
+    let notes = ctx.data().conn().await.get_notes_for_challenge(challenge.id).await?;
+
+    // defer because uploading the files may take longer than 3 seconds
+    ctx.defer().await?;
+
+    let reply = CreateReply::default().embed(notes_embed(&challenge, &notes));
+    let files = notes.iter()
+        .filter_map(|note| note.file.as_ref())
+        .map(|file| CreateAttachment::bytes(file.data.clone(), &file.name))
+        .collect();
+
+    send_with_files(&ctx, reply, files).await?;
+
+    Ok(())
+}
+
+/// Pins a digest of the notes in the current channel which is updated when notes are added
+#[poise::command(slash_command)]
+pub async fn digest(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let challenge = get_challenge_from_ctx(&ctx).await?;
+
+    let mut conn = ctx.data().conn().await;
+    let notes = conn.get_notes_for_challenge(challenge.id).await?;
+
+    pin_digest(&ctx, &mut conn, &challenge, &notes).await?;
+
+    conn.commit().await?;
+
+    let reply = CreateReply::default()
+        .content("Pinned notes digest")
+        .ephemeral(true);
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Sends and pins a digest of the notes in the challenge channel, and saves it as the digest to keep up to date
+async fn pin_digest(ctx: &CmdContext<'_>, conn: &mut DbConn<'_>, challenge: &Challenge, notes: &[Note]) -> Result<(), Error> {
+    let channel_id = challenge.channel_id
+        .ok_or_else(|| anyhow::anyhow!("You are not inside a challenge channel."))?;
+
+    let message = CreateMessage::new().embed(notes_embed(challenge, notes));
+    let message = channel_id.send_message(ctx, message).await?;
+    message.pin(ctx).await?;
+
+    conn.set_challenge_notes_message(challenge.id, message.id).await?;
+
+    Ok(())
+}
+
+fn notes_embed(challenge: &Challenge, notes: &[Note]) -> CreateEmbed {
+    let mut description = String::new();
+
+    for (i, note) in notes.iter().enumerate() {
+        let mut line = format!("**#{}** {} - {}", note.id, note.content, note.user_id.mention());
+
+        if let Some(file) = &note.file {
+            line.push_str(&format!(" (`{}`)", file.name));
+        }
+        line.push('\n');
+
+        if description.len() + line.len() > EMBED_DESCRIPTION_LIMIT - 64 {
+            description.push_str(&format!("...and {} more", notes.len() - i));
+            break;
+        }
+
+        description.push_str(&line);
+    }
+
+    if description.is_empty() {
+        description.push_str("No notes yet, use `/note add` to add one");
+    }
+
+    CreateEmbed::new()
+        .title(format!("Notes for {}/{}", challenge.category, challenge.name))
+        .description(description)
+        .color(0xc22026)
+}
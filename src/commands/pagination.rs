@@ -0,0 +1,72 @@
+//! Embeds with buttons to flip between pages
+//!
+//! Button presses are handled by a collector instead of the event handler,
+//! so button ids start with [`PAGE_BUTTON_PREFIX`] to never collide with buttons the event handler dispatches.
+
+use std::time::Duration;
+
+use poise::CreateReply;
+use serenity::all::{ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage};
+
+use super::{CmdContext, Error};
+
+/// Prefix of the custom id of page buttons, followed by the command invocation id
+pub const PAGE_BUTTON_PREFIX: &str = "page:";
+
+/// How long the buttons keep working after the last press
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Sends the first page with buttons to go to the other pages, and flips pages until the buttons time out
+pub async fn paginate_embeds(ctx: CmdContext<'_>, pages: Vec<CreateEmbed>) -> Result<(), Error> {
+    let page_count = pages.len();
+    let pages: Vec<CreateEmbed> = pages.into_iter()
+        .enumerate()
+        .map(|(i, page)| page.footer(CreateEmbedFooter::new(format!("Page {}/{page_count}", i + 1))))
+        .collect();
+
+    let Some(first_page) = pages.first() else {
+        return Ok(());
+    };
+
+    if page_count == 1 {
+        ctx.send(CreateReply::default().embed(first_page.clone())).await?;
+        return Ok(());
+    }
+
+    let button_id_prefix = format!("{PAGE_BUTTON_PREFIX}{}:", ctx.id());
+    let prev_button_id = format!("{button_id_prefix}prev");
+    let next_button_id = format!("{button_id_prefix}next");
+
+    let buttons = vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(&prev_button_id).emoji('◀').style(ButtonStyle::Secondary),
+        CreateButton::new(&next_button_id).emoji('▶').style(ButtonStyle::Secondary),
+    ])];
+
+    let reply = ctx.send(CreateReply::default().embed(first_page.clone()).components(buttons)).await?;
+
+    let mut current_page = 0;
+    loop {
+        let button_id_prefix = button_id_prefix.clone();
+        let Some(press) = ComponentInteractionCollector::new(ctx)
+            .filter(move |press| press.data.custom_id.starts_with(&button_id_prefix))
+            .timeout(PAGE_TIMEOUT)
+            .await
+        else {
+            break;
+        };
+
+        if press.data.custom_id == next_button_id {
+            current_page = (current_page + 1) % page_count;
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(page_count - 1);
+        }
+
+        let response = CreateInteractionResponseMessage::new().embed(pages[current_page].clone());
+        press.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+    }
+
+    // remove buttons which don't work anymore
+    reply.edit(ctx, CreateReply::default().embed(pages[current_page].clone()).components(Vec::new())).await?;
+
+    Ok(())
+}
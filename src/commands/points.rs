@@ -0,0 +1,195 @@
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateMessage, FormattedTimestamp, FormattedTimestampStyle, Mentionable, UserId};
+
+use crate::config::config;
+use crate::db::PointsReason;
+use crate::points::{give_points, points_to_string, set_points};
+
+use super::{CmdContext, Error, has_perms};
+
+/// Number of point changes shown by `/points history`
+const HISTORY_LENGTH: u32 = 15;
+
+#[poise::command(slash_command, subcommands("grant", "deduct", "set", "history", "rebuild"))]
+pub async fn points(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Converts points entered by a user, like `1.5`, to the stored points, which are 10 times larger
+fn parse_points(points: f64) -> i64 {
+    (points * 10.0).round() as i64
+}
+
+/// Gives points to a user
+#[poise::command(slash_command)]
+pub async fn grant(
+    ctx: CmdContext<'_>,
+    #[description = "User to give points to"] user: UserId,
+    #[description = "Number of points to give"]
+    #[min = 0.1]
+    points: f64,
+    #[description = "Why the points are being given"] reason: String,
+) -> Result<(), Error> {
+    change_points(ctx, user, PointsChange::By(parse_points(points)), reason).await
+}
+
+/// Takes points away from a user
+#[poise::command(slash_command)]
+pub async fn deduct(
+    ctx: CmdContext<'_>,
+    #[description = "User to take points from"] user: UserId,
+    #[description = "Number of points to take away"]
+    #[min = 0.1]
+    points: f64,
+    #[description = "Why the points are being taken away"] reason: String,
+) -> Result<(), Error> {
+    change_points(ctx, user, PointsChange::By(-parse_points(points)), reason).await
+}
+
+/// Sets a user's points to an exact amount
+#[poise::command(slash_command)]
+pub async fn set(
+    ctx: CmdContext<'_>,
+    #[description = "User to set the points of"] user: UserId,
+    #[description = "Number of points the user should have"]
+    #[min = 0.0]
+    points: f64,
+    #[description = "Why the points are being changed"] reason: String,
+) -> Result<(), Error> {
+    if points < 0.0 {
+        return Err(anyhow::anyhow!("Points can not be set below 0."));
+    }
+
+    change_points(ctx, user, PointsChange::To(parse_points(points)), reason).await
+}
+
+enum PointsChange {
+    /// Add to the user's points, or take away if negative
+    By(i64),
+    /// Set the user's points to exactly this
+    To(i64),
+}
+
+/// Changes a user's points, and announces the change in the bot log channel
+async fn change_points(ctx: CmdContext<'_>, user_id: UserId, change: PointsChange, note: String) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to change points."));
+    }
+
+    let officer_id = ctx.author().id;
+
+    let mut conn = ctx.data().conn().await;
+    let delta = match change {
+        PointsChange::By(delta) => {
+            let reason = if delta > 0 {
+                PointsReason::ManualGrant { note: note.clone() }
+            } else {
+                PointsReason::Revocation { note: note.clone() }
+            };
+
+            give_points(ctx.serenity_context(), &mut conn, user_id, delta, &reason, Some(officer_id)).await?;
+            delta
+        },
+        PointsChange::To(points) => match set_points(ctx.serenity_context(), &mut conn, user_id, points, &note, officer_id).await? {
+            Some(delta) => delta,
+            None => {
+                ctx.say(format!("{} already has that many points.", user_id.mention())).await?;
+                return Ok(());
+            },
+        },
+    };
+    conn.commit().await?;
+
+    let (reason, action) = if delta > 0 {
+        (PointsReason::ManualGrant { note }, "gave")
+    } else {
+        (PointsReason::Revocation { note }, "took")
+    };
+
+    let change_message = format!(
+        "{} {action} {} points {} {}",
+        officer_id.mention(),
+        points_to_string(delta.abs()),
+        if delta > 0 { "to" } else { "from" },
+        user_id.mention(),
+    );
+
+    let log_embed = CreateEmbed::new()
+        .title("Points Changed")
+        .description(format!("{change_message}\n\n**Reason**: {reason}"))
+        .color(0xc22026);
+
+    config().server.bot_log_channel
+        .send_message(ctx, CreateMessage::new().add_embed(log_embed))
+        .await?;
+
+    ctx.say(format!("{change_message}.")).await?;
+
+    Ok(())
+}
+
+/// Lists the most recent changes to your points, or someone else's
+#[poise::command(slash_command)]
+pub async fn history(
+    ctx: CmdContext<'_>,
+    #[description = "User to show point history for, defaults to you"] user: Option<UserId>,
+) -> Result<(), Error> {
+    let user_id = user.unwrap_or(ctx.author().id);
+
+    let transactions = ctx.data().conn().await
+        .get_point_transactions_for_user(user_id, HISTORY_LENGTH)
+        .await?;
+
+    let mut description = String::new();
+    for transaction in transactions.iter() {
+        let sign = if transaction.delta >= 0 { "+" } else { "-" };
+
+        // legacy points have no real date, they are recorded at the unix epoch
+        let date = match transaction.reason {
+            PointsReason::Legacy => String::from("before history"),
+            _ => FormattedTimestamp::new(transaction.created_at, Some(FormattedTimestampStyle::ShortDate)).to_string(),
+        };
+
+        description.push_str(&format!(
+            "`#{}` {date} **{sign}{}** {}",
+            transaction.id,
+            points_to_string(transaction.delta.abs()),
+            transaction.reason,
+        ));
+
+        if let Some(actor_id) = transaction.actor_id {
+            description.push_str(&format!(" by {}", actor_id.mention()));
+        }
+
+        description.push('\n');
+    }
+
+    if description.is_empty() {
+        description.push_str("No points earned yet");
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Point History")
+        .description(format!("Recent point changes for {}\n\n{description}", user_id.mention()))
+        .color(0xc22026);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Recomputes everyone's points from the point history
+#[poise::command(slash_command)]
+pub async fn rebuild(ctx: CmdContext<'_>) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to rebuild points."));
+    }
+
+    let mut conn = ctx.data().conn().await;
+    let changed_count = conn.rebuild_points_from_ledger().await?;
+    conn.commit().await?;
+
+    ctx.say(format!("Rebuilt points from history, {changed_count} users had their points corrected.")).await?;
+
+    Ok(())
+}
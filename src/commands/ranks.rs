@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use poise::CreateReply;
+use serenity::all::{Member, Mentionable, UserId};
+use tracing::error;
+
+use crate::config::config;
+use crate::points::{Rank, RankManager};
+
+use super::{CmdContext, Error, has_perms, role_id_for_role_name};
+
+/// Max number of members discord returns in one request
+const MEMBER_PAGE_SIZE: u64 = 1000;
+
+/// Most users whose roles couldn't be changed listed by name, so the message stays under discord's length limit
+const MAX_LISTED_FAILURES: usize = 20;
+
+#[poise::command(slash_command, subcommands("resync", "announcements"))]
+pub async fn ranks(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Fixes everyone's saved rank and rank roles to match their points and the rank policy
+#[poise::command(slash_command)]
+pub async fn resync(ctx: CmdContext<'_>) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to resync ranks."));
+    }
+
+    // Defer response because updating everyone's roles will take longer than 3 seconds
+    ctx.defer().await?;
+
+    let rank_sync = sync_ranks(&ctx, false).await?;
+
+    ctx.say(format!(
+        "Resynced ranks, changed {} ranks and {} roles.{}",
+        rank_sync.rank_changes,
+        rank_sync.role_changes,
+        rank_sync.failures_message(),
+    )).await?;
+
+    Ok(())
+}
+
+/// What changed when ranks were synced
+pub struct RankSync {
+    pub rank_changes: u32,
+    pub role_changes: u32,
+    /// Users whose roles could not be changed, their saved rank was still changed
+    pub failed_users: Vec<UserId>,
+}
+
+impl RankSync {
+    /// Lists the users whose roles could not be changed, empty if there were none
+    pub fn failures_message(&self) -> String {
+        if self.failed_users.is_empty() {
+            return String::new();
+        }
+
+        let mut mentions = self.failed_users.iter()
+            .take(MAX_LISTED_FAILURES)
+            .map(|user_id| user_id.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if self.failed_users.len() > MAX_LISTED_FAILURES {
+            mentions.push_str(&format!(" and {} others", self.failed_users.len() - MAX_LISTED_FAILURES));
+        }
+
+        format!("\nCould not change the roles of {mentions}, run `/ranks resync` to try again.")
+    }
+}
+
+/// Fixes everyone's saved rank and rank roles, if `reset` is true ranks kept by the sticky rank policy are dropped
+/// 
+/// Ranks are saved before any roles are changed, so a role which can't be changed doesn't undo the rank changes.
+pub async fn sync_ranks(ctx: &CmdContext<'_>, reset: bool) -> anyhow::Result<RankSync> {
+    let members = get_all_members(ctx).await?;
+
+    let mut rank_role_ids = Vec::new();
+    for rank_name in config().ranks.rank_names.iter() {
+        rank_role_ids.push(role_id_for_role_name(ctx.serenity_context(), rank_name).await?);
+    }
+
+    let mut rank_changes = 0;
+    let mut ranks = Vec::new();
+
+    {
+        let mut conn = ctx.data().conn().await;
+
+        let rank_manager = RankManager::new(&mut conn).await?;
+
+        for user in conn.get_all_users().await? {
+            let kept_rank = if reset { Rank::Unranked } else { user.rank };
+            let rank = rank_manager.resynced_rank(user.rank_points(), kept_rank);
+
+            if rank != user.rank {
+                conn.set_rank(user.id, rank).await?;
+                rank_changes += 1;
+            }
+
+            ranks.push((user.id, rank));
+        }
+
+        conn.commit().await?;
+    }
+
+    let mut role_changes = 0;
+    let mut failed_users = Vec::new();
+
+    for (user_id, rank) in ranks {
+        // user is no longer in the server, so they have no roles to fix
+        let Some(member) = members.get(&user_id) else {
+            continue;
+        };
+
+        for (i, role_id) in rank_role_ids.iter().enumerate() {
+            let Some(role_id) = role_id else {
+                continue;
+            };
+
+            let should_have_role = rank == Rank::Rank(i);
+            let has_role = member.roles.contains(role_id);
+
+            let result = if should_have_role && !has_role {
+                member.add_role(ctx, role_id).await
+            } else if !should_have_role && has_role {
+                member.remove_role(ctx, role_id).await
+            } else {
+                continue;
+            };
+
+            match result {
+                Ok(()) => role_changes += 1,
+                Err(e) => {
+                    error!("failed to change rank roles for {user_id}: {e}");
+                    failed_users.push(user_id);
+                    break;
+                },
+            }
+        }
+    }
+
+    Ok(RankSync {
+        rank_changes,
+        role_changes,
+        failed_users,
+    })
+}
+
+/// Turns public announcements of your rank ups on or off
+#[poise::command(slash_command)]
+pub async fn announcements(
+    ctx: CmdContext<'_>,
+    #[description = "Whether your rank ups are posted in the rank up channel"] enabled: bool,
+) -> Result<(), Error> {
+    let mut conn = ctx.data().conn().await;
+    conn.set_announce_rank_ups(ctx.author().id, enabled).await?;
+    conn.commit().await?;
+
+    let content = if enabled {
+        "Your rank ups will be announced."
+    } else {
+        "Your rank ups will no longer be announced, you will still get rank roles."
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
+
+async fn get_all_members(ctx: &CmdContext<'_>) -> anyhow::Result<HashMap<UserId, Member>> {
+    let mut members = HashMap::new();
+    let mut after = None;
+
+    loop {
+        let page = config().server.guild_id.members(ctx, Some(MEMBER_PAGE_SIZE), after).await?;
+        after = page.last().map(|member| member.user.id);
+
+        let page_len = page.len();
+        members.extend(page.into_iter().map(|member| (member.user.id, member)));
+
+        if page_len < MEMBER_PAGE_SIZE as usize {
+            break;
+        }
+    }
+
+    Ok(members)
+}
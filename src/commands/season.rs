@@ -0,0 +1,222 @@
+use chrono::{NaiveDate, Utc};
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateMessage, FormattedTimestamp, FormattedTimestampStyle, Mentionable, Timestamp};
+
+use crate::config::config;
+use crate::db::{Season, SeasonStanding};
+use crate::points::points_to_string;
+use crate::semester::Semester;
+
+use super::{CmdContext, Error, has_perms, ranks::sync_ranks};
+
+/// Number of users shown on a season leaderboard
+const LEADERBOARD_LENGTH: u32 = 10;
+
+#[poise::command(slash_command, subcommands("start", "close", "leaderboard", "list"))]
+pub async fn season(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Starts a new season, points earned between its start and end date count towards it
+#[poise::command(slash_command)]
+pub async fn start(
+    ctx: CmdContext<'_>,
+    #[description = "Name of the season, defaults to the current semester like `Fall 2026`"] name: Option<String>,
+    #[description = "First day of the season as YYYY-MM-DD, defaults to now"] start_date: Option<String>,
+    #[description = "Last day of the season as YYYY-MM-DD, defaults to running until it is closed"] end_date: Option<String>,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to start a season."));
+    }
+
+    let start_at = match start_date {
+        Some(start_date) => parse_date(&start_date)?,
+        None => Utc::now().timestamp(),
+    };
+
+    // the season includes the whole last day, so it ends at the start of the next day
+    let end_at = match end_date {
+        Some(end_date) => Some(parse_date(&end_date)? + 24 * 60 * 60),
+        None => None,
+    };
+
+    if end_at.is_some_and(|end_at| end_at <= start_at) {
+        return Err(anyhow::anyhow!("The season must end after it starts."));
+    }
+
+    let mut conn = ctx.data().conn().await;
+
+    if let Some(season) = conn.get_current_season().await? {
+        return Err(anyhow::anyhow!("{} is still running, close it with `/season close` first.", season.name));
+    }
+
+    let name = name.unwrap_or_else(|| Semester::from_timestamp(Timestamp::now()).to_string());
+
+    let season = conn.create_season(&name, start_at, end_at).await
+        .or(Err(anyhow::anyhow!("A season named {name} already exists.")))?;
+
+    conn.commit().await?;
+
+    ctx.say(format!("Started the {} season.", season.name)).await?;
+
+    Ok(())
+}
+
+/// Closes the current season and saves its final standings
+#[poise::command(slash_command)]
+pub async fn close(
+    ctx: CmdContext<'_>,
+    #[description = "Reset everyone's rank and roles, only points earned from now on count towards ranks"] reset_ranks: Option<bool>,
+) -> Result<(), Error> {
+    if !has_perms(&ctx).await {
+        return Err(anyhow::anyhow!("You do not have permissions to close a season."));
+    }
+
+    let reset_ranks = reset_ranks.unwrap_or(false);
+    if reset_ranks {
+        // Defer response because resetting everyone's roles will take longer than 3 seconds
+        ctx.defer().await?;
+    }
+
+    let mut conn = ctx.data().conn().await;
+
+    let season = conn.get_current_season().await?
+        .ok_or_else(|| anyhow::anyhow!("There is no season running."))?;
+
+    let season = conn.close_season(season.id).await?;
+    let standings = conn.get_season_standings(season.id, LEADERBOARD_LENGTH).await?;
+
+    if reset_ranks {
+        conn.reset_ranks().await?;
+    }
+
+    conn.commit().await?;
+
+    config().server.rank_up_channel
+        .send_message(ctx, CreateMessage::new().embed(leaderboard_embed(&season, &standings)))
+        .await?;
+
+    if reset_ranks {
+        let rank_sync = sync_ranks(&ctx, true).await?;
+
+        ctx.say(format!(
+            "Closed the {} season, reset {} ranks and {} roles.{}",
+            season.name,
+            rank_sync.rank_changes,
+            rank_sync.role_changes,
+            rank_sync.failures_message(),
+        )).await?;
+    } else {
+        ctx.say(format!("Closed the {} season.", season.name)).await?;
+    }
+
+    Ok(())
+}
+
+/// Lists the top point earners of a season
+#[poise::command(slash_command)]
+pub async fn leaderboard(
+    ctx: CmdContext<'_>,
+    #[description = "Name of the season, defaults to the current or most recent season"] season: Option<String>,
+) -> Result<(), Error> {
+    let mut conn = ctx.data().conn().await;
+
+    let season = match season {
+        Some(name) => conn.get_season_by_name(&name).await
+            .or(Err(anyhow::anyhow!("There is no season named {name}, use `/season list` to see every season.")))?,
+        None => conn.get_seasons().await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No seasons have been started yet."))?,
+    };
+
+    // closed seasons use their saved standings, so later changes to the ledger don't rewrite history
+    let standings = if season.is_closed() {
+        conn.get_season_standings(season.id, LEADERBOARD_LENGTH).await?
+    } else {
+        conn.get_season_points(&season, LEADERBOARD_LENGTH).await?
+    };
+
+    ctx.send(CreateReply::default().embed(leaderboard_embed(&season, &standings))).await?;
+
+    Ok(())
+}
+
+/// Lists every season
+#[poise::command(slash_command)]
+pub async fn list(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let seasons = ctx.data().conn().await.get_seasons().await?;
+
+    let mut description = String::new();
+    for season in seasons.iter() {
+        let start = FormattedTimestamp::new(season.start_at, Some(FormattedTimestampStyle::ShortDate));
+
+        match season.points_end() {
+            Some(end_at) => description.push_str(&format!(
+                "**{}** {start} - {}\n",
+                season.name,
+                FormattedTimestamp::new(end_at, Some(FormattedTimestampStyle::ShortDate)),
+            )),
+            None => description.push_str(&format!("**{}** {start} - now\n", season.name)),
+        }
+    }
+
+    if description.is_empty() {
+        description.push_str("No seasons have been started yet");
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Seasons")
+        .description(description)
+        .color(0xc22026);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn leaderboard_embed(season: &Season, standings: &[SeasonStanding]) -> CreateEmbed {
+    let description = if season.is_closed() {
+        format!("Final standings of the {} season", season.name)
+    } else {
+        format!("Here is the current leaderboard for the {} season", season.name)
+    };
+
+    let mut users = String::new();
+    let mut points = String::new();
+
+    for standing in standings {
+        let position = match standing.placement {
+            1 => "🥇".to_string(),
+            2 => "🥈".to_string(),
+            3 => "🥉".to_string(),
+            placement => format!("{placement}. "),
+        };
+
+        users.push_str(&format!("{position}{}\n", standing.user_id.mention()));
+        points.push_str(&format!("{}\n", points_to_string(standing.points)));
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("{} Leaderboard", season.name))
+        .description(description)
+        .color(0xc22026);
+
+    if standings.is_empty() {
+        embed.field("Users", "Nobody has earned points yet", false)
+    } else {
+        embed.field("Users", users, true)
+            .field("Points", points, true)
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date into the unix timestamp of the start of that day in UTC
+fn parse_date(date: &str) -> Result<i64, Error> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or(Err(anyhow::anyhow!("{date} is not a valid date, use the format YYYY-MM-DD.")))?;
+
+    Ok(date.and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+        .timestamp())
+}
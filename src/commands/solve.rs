@@ -1,8 +1,9 @@
-use serenity::all::{ButtonStyle, ComponentInteraction, ComponentInteractionDataKind, Context, CreateButton, CreateEmbed, CreateMessage, EditMessage, EditThread, Mentionable, Message, UserId};
+use serenity::all::{ButtonStyle, CreateActionRow, ComponentInteraction, ComponentInteractionDataKind, Context, CreateButton, CreateEmbed, CreateMessage, EditMessage, EditThread, Mentionable, Message, Timestamp, UserId};
 
+use crate::achievements::check_solve_achievements;
 use crate::config::config;
 use crate::db::{ApprovalStatus, Challenge, ChallengeType, Competition, Solve};
-use crate::points::check_rank_up;
+use crate::points::check_rank_change;
 
 use super::{CmdContext, CommandContext, Error, competition::{get_competition_from_ctx, get_challenge_from_ctx}};
 
@@ -72,6 +73,8 @@ pub async fn solve(
         approval_message_id: approval_message.id,
         flag,
         approval_status: ApprovalStatus::Pending,
+        created_at: Timestamp::now(),
+        approved_at: None,
     };
 
     let mut conn = ctx.data().conn().await;
@@ -135,6 +138,8 @@ pub async fn quick_solve(
         name: name.clone(),
         category,
         channel_id: None,
+        notes_message_id: None,
+        created_at: Timestamp::now(),
     };
     challenge.id = conn.create_challenge(challenge.clone()).await?;
     
@@ -152,6 +157,8 @@ pub async fn quick_solve(
         approval_message_id: approval_message.id,
         flag,
         approval_status: ApprovalStatus::Pending,
+        created_at: Timestamp::now(),
+        approved_at: None,
     };
 
     let solve_id = conn.create_solve(solve, &solver_ids).await?;
@@ -187,25 +194,45 @@ async fn send_approval_message(
         .field("Flag", format!("```{flag}```"), false)
         .field("Participants", teammate_string, false);
 
-    let accept_button = CreateButton::new("accept")
+    let approval_message = CreateMessage::new()
+        .add_embed(approval_embed)
+        .components(approval_buttons("accept", "reject"));
+
+    let approval_message = config().server.solve_approvals_channel_id
+        .send_message(ctx, approval_message).await?;
+
+    Ok(approval_message)
+}
+
+/// Creates a row with accept and reject buttons with the given custom ids
+pub fn approval_buttons(accept_id: &str, reject_id: &str) -> Vec<CreateActionRow> {
+    let accept_button = CreateButton::new(accept_id)
         .label("Accept")
         .emoji('✅')
         .style(ButtonStyle::Success);
 
-    let reject_button = CreateButton::new("reject")
+    let reject_button = CreateButton::new(reject_id)
         .label("Reject")
         .emoji('❎')
         .style(ButtonStyle::Danger);
 
-    let approval_message = CreateMessage::new()
-        .add_embed(approval_embed)
-        .button(accept_button)
-        .button(reject_button);
+    vec![CreateActionRow::Buttons(vec![accept_button, reject_button])]
+}
 
-    let approval_message = config().server.solve_approvals_channel_id
-        .send_message(ctx, approval_message).await?;
+/// Marks an approval message as approved or declined by `officer_id` and removes its buttons
+pub async fn close_approval_message(context: &Context, message: &mut Message, status: ApprovalStatus, officer_id: UserId) -> anyhow::Result<()> {
+    let action = match status {
+        ApprovalStatus::Approved => "approved",
+        _ => "declined",
+    };
 
-    Ok(approval_message)
+    let edit = EditMessage::new()
+        .content(format!("This request is {action} by {}", officer_id.mention()))
+        .components(Vec::new());
+
+    message.edit(context, edit).await?;
+
+    Ok(())
 }
 
 /// Recieves Component Interaction events and updates solve status if they are an approval button
@@ -215,39 +242,39 @@ pub async fn handle_approval_button(context: &Context, cmd_context: &CommandCont
     if matches!(interaction.data.kind, ComponentInteractionDataKind::Button) {
         let mut message = interaction.message.clone();
         let mut solve = conn.get_solve_by_approval_message_id(message.id).await?;
+        // people who earned points for the solve, empty unless it was just approved
+        let mut solver_ids = Vec::new();
 
         if solve.approval_status != ApprovalStatus::Pending {
             message.reply(context, format!("solve is alredy {}", solve.approval_status)).await?;
         } else if interaction.data.custom_id == "accept" {
             solve.approval_status = ApprovalStatus::Approved;
+            solve.approved_at = Some(Timestamp::now());
 
             // give participants points for solving
-            let points_updates = conn.give_points_for_solve(solve.id, config().ranks.points_per_solve).await?;
+            let points_updates = conn.give_points_for_solve(solve.id, config().ranks.points_per_solve, interaction.user.id).await?;
+
+            solver_ids = points_updates.iter().map(|update| update.user_id).collect();
 
             // rank people up as necassary
             for points_update in points_updates {
-                check_rank_up(context, &mut conn, points_update).await?;
+                check_rank_change(context, &mut conn, points_update).await?;
             }
 
-            let edit = EditMessage::new()
-                .content(format!("This request is approved by {}", interaction.user.id.mention()))
-                .components(Vec::new());
-
-            message.edit(context, edit).await?;
-
-
+            close_approval_message(context, &mut message, solve.approval_status, interaction.user.id).await?;
         } else if interaction.data.custom_id == "reject" {
             solve.approval_status = ApprovalStatus::Declined;
 
-            let edit = EditMessage::new()
-                .content(format!("This request is declined by {}", interaction.user.id.mention()))
-                .components(Vec::new());
-
-            message.edit(context, edit).await?;
+            close_approval_message(context, &mut message, solve.approval_status, interaction.user.id).await?;
         }
 
         // save updated approval status
-        conn.update_solve(solve).await?;
+        conn.update_solve(solve.clone()).await?;
+
+        // achievements need the solve to be saved as approved
+        if !solver_ids.is_empty() {
+            check_solve_achievements(context, &mut conn, &solve, &solver_ids).await?;
+        }
 
         conn.commit().await?;
 
@@ -1,14 +1,30 @@
-use poise::CreateReply;
-use serenity::all::{CreateEmbed, Mentionable};
+use std::collections::{hash_map::Entry, HashMap};
+
+use chrono::{Datelike, TimeZone, Utc};
+use poise::{ChoiceParameter, CreateReply};
+use serenity::all::{ChannelId, CreateAttachment, CreateEmbed, CreateEmbedFooter, FormattedTimestamp, FormattedTimestampStyle, Mentionable, Timestamp, UserId};
 use strum::IntoEnumIterator;
 
+use crate::charts::{bar_chart, line_chart};
 use crate::config::config;
-use crate::points::{get_point_cutoffs, points_to_string};
-use crate::db::ChallengeType;
+use crate::points::{cutoff_mode_description, get_point_cutoffs, points_to_string, Rank};
+use crate::db::{ChallengeType, DbConn, PointsReason};
+use crate::semester::Semester;
+
+use super::{CmdContext, Error, has_perms, pagination::paginate_embeds};
+
+/// Number of users listed on one page of `/stats leaderboard`
+const LEADERBOARD_PAGE_LENGTH: usize = 10;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
 
-use super::{CmdContext, Error};
+/// Number of competitions and recent solves listed on one page of `/stats profile`
+const PROFILE_PAGE_LENGTH: usize = 15;
 
-#[poise::command(slash_command, subcommands("solves", "leaderboard", "rank"))]
+/// Number of challenges listed by activity in `/stats server`
+const LISTED_CHALLENGE_COUNT: u32 = 5;
+
+#[poise::command(slash_command, subcommands("solves", "leaderboard", "rank", "profile", "chart", "server"))]
 pub async fn stats(_ctx: CmdContext<'_>) -> Result<(), Error> {
     Ok(())
 }
@@ -40,41 +56,132 @@ pub async fn solves(ctx: CmdContext<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum LeaderboardKind {
+    #[name = "points"]
+    Points,
+    #[name = "solves"]
+    Solves,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum TimePeriod {
+    #[name = "all time"]
+    AllTime,
+    #[name = "last 30 days"]
+    Last30Days,
+    #[name = "this semester"]
+    ThisSemester,
+}
+
+impl TimePeriod {
+    /// Gets the unix timestamp the period starts at, `None` for all time
+    fn start(&self) -> Option<i64> {
+        match self {
+            Self::AllTime => None,
+            Self::Last30Days => Some(Timestamp::now().unix_timestamp() - 30 * SECONDS_PER_DAY),
+            Self::ThisSemester => Some(Semester::current().start().unix_timestamp()),
+        }
+    }
+}
+
 /// Lists the top point leaders on the server
 #[poise::command(slash_command)]
-pub async fn leaderboard(ctx: CmdContext<'_>) -> Result<(), Error> {
-    let mut embed = CreateEmbed::new()
-        .title("b01lers Leaderboard")
-        .description("Here is the current leaderboard on the server")
-        .color(0xc22026)
-        .thumbnail("https://pbs.twimg.com/profile_images/568451513295441921/9Hm60msK_400x400.png");
+pub async fn leaderboard(
+    ctx: CmdContext<'_>,
+    #[description = "Rank by points or by challenges solved, category and competition filters always rank by solves"]
+    board: Option<LeaderboardKind>,
+    #[description = "Only count solves in this category"] category: Option<ChallengeType>,
+    #[description = "Only count solves in this competition"] competition: Option<ChannelId>,
+    #[description = "Only count points or solves from this time period"] period: Option<TimePeriod>,
+) -> Result<(), Error> {
+    let period = period.unwrap_or(TimePeriod::AllTime);
 
-    let mut users = String::new();
-    let mut points = String::new();
+    let board = if category.is_some() || competition.is_some() {
+        LeaderboardKind::Solves
+    } else {
+        board.unwrap_or(LeaderboardKind::Points)
+    };
 
-    for (i, user) in ctx.data().conn().await.get_users_by_points(10).await?.iter().enumerate() {
-        let position = match i {
-            0 => "🥇".to_string(),
-            1 => "🥈".to_string(),
-            2 => "🥉".to_string(),
-            _ => format!("{}. ", i + 1),
-        };
+    let entries = {
+        let mut conn = ctx.data().conn().await;
 
-        users.push_str(&format!("{position}{}\n", user.id.mention()));
-        points.push_str(&format!("{}\n", points_to_string(user.points)));
+        match board {
+            LeaderboardKind::Points => conn.get_points_leaderboard(period.start()).await?,
+            LeaderboardKind::Solves => conn.get_solves_leaderboard(category, competition, period.start()).await?,
+        }
+    };
+
+    let mut filters = vec![period.name().to_string()];
+    if let Some(category) = category {
+        filters.push(category.to_string());
+    }
+    if let Some(competition) = competition {
+        filters.push(competition.mention().to_string());
     }
 
-    embed = embed.field("Users", users, true)
-        .field("Points", points, true);
+    let score_name = match board {
+        LeaderboardKind::Points => "Points",
+        LeaderboardKind::Solves => "Solves",
+    };
+    let format_score = |score: i64| match board {
+        LeaderboardKind::Points => points_to_string(score),
+        LeaderboardKind::Solves => score.to_string(),
+    };
 
-    let message = CreateReply::default()
-        .embed(embed);
+    let author_id = ctx.author().id;
+    let author_position = match entries.iter().position(|entry| entry.user_id == author_id) {
+        Some(i) => format!("#{} with {} {}", i + 1, format_score(entries[i].score), score_name.to_lowercase()),
+        None => "Not on this leaderboard yet".to_string(),
+    };
 
-    ctx.send(message).await?;
+    let mut pages = Vec::new();
+    for (page_number, page_entries) in entries.chunks(LEADERBOARD_PAGE_LENGTH).enumerate() {
+        let mut users = String::new();
+        let mut scores = String::new();
+
+        for (i, entry) in page_entries.iter().enumerate() {
+            let i = page_number * LEADERBOARD_PAGE_LENGTH + i;
+            let position = match i {
+                0 => "🥇".to_string(),
+                1 => "🥈".to_string(),
+                2 => "🥉".to_string(),
+                _ => format!("{}. ", i + 1),
+            };
+
+            // highlight the person who ran the command
+            if entry.user_id == author_id {
+                users.push_str(&format!("**{position}{}**\n", entry.user_id.mention()));
+                scores.push_str(&format!("**{}**\n", format_score(entry.score)));
+            } else {
+                users.push_str(&format!("{position}{}\n", entry.user_id.mention()));
+                scores.push_str(&format!("{}\n", format_score(entry.score)));
+            }
+        }
+
+        pages.push(leaderboard_page(&filters)
+            .field("Users", users, true)
+            .field(score_name, scores, true)
+            .field("Your Position", &author_position, false));
+    }
+
+    if pages.is_empty() {
+        pages.push(leaderboard_page(&filters).field("Users", "Nobody is on this leaderboard yet", false));
+    }
+
+    paginate_embeds(ctx, pages).await?;
 
     Ok(())
 }
 
+fn leaderboard_page(filters: &[String]) -> CreateEmbed {
+    CreateEmbed::new()
+        .title("b01lers Leaderboard")
+        .description(format!("Here is the current leaderboard on the server ({})", filters.join(", ")))
+        .color(0xc22026)
+        .thumbnail("https://pbs.twimg.com/profile_images/568451513295441921/9Hm60msK_400x400.png")
+}
+
 /// Lists your points and the point requirements of other ranks
 #[poise::command(slash_command)]
 pub async fn rank(ctx: CmdContext<'_>) -> Result<(), Error> {
@@ -84,9 +191,13 @@ pub async fn rank(ctx: CmdContext<'_>) -> Result<(), Error> {
 
     let mut embed = CreateEmbed::new()
         .title("Server Rank")
-        .description("Points can be earned through participation in the server, like sending messages or solving CTF challenges.")
+        .description(format!(
+            "Points can be earned through participation in the server, like sending messages or solving CTF challenges.\n\n{}",
+            cutoff_mode_description(),
+        ))
         .color(0xc22026)
-        .field("Point Total", points_to_string(user.points), true);
+        .field("Point Total", points_to_string(user.points), true)
+        .field("Rank Points", points_to_string(user.rank_points()), true);
 
     let cutoffs = get_point_cutoffs(&mut conn).await?;
     let rank_names = &config().ranks.rank_names;
@@ -100,4 +211,304 @@ pub async fn rank(ctx: CmdContext<'_>) -> Result<(), Error> {
     ctx.send(message).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Shows the points, rank, and solves of you or someone else
+#[poise::command(slash_command)]
+pub async fn profile(
+    ctx: CmdContext<'_>,
+    #[description = "User to show the profile of, defaults to you"] user: Option<UserId>,
+) -> Result<(), Error> {
+    let user_id = user.unwrap_or(ctx.author().id);
+    let user = user_id.to_user(ctx).await?;
+
+    // read everything up front, so the connection isn't held while the pages are shown
+    let (points, rank, is_verified, solves, achievements, competition_names) = {
+        let mut conn = ctx.data().conn().await;
+
+        let (points, rank, is_verified) = match conn.get_user_by_id(user_id).await {
+            Ok(db_user) => (db_user.points, db_user.rank, db_user.is_verified()),
+            // user has never earned points
+            Err(_) => (0, Rank::Unranked, false),
+        };
+
+        let solves = conn.get_solved_challenges_for_user(user_id).await?;
+        let achievements = conn.get_achievements_for_user(user_id).await?;
+
+        let mut competition_names: HashMap<ChannelId, String> = HashMap::new();
+        for solve in solves.iter() {
+            if let Entry::Vacant(entry) = competition_names.entry(solve.competition_id) {
+                let name = match conn.get_competition(solve.competition_id).await {
+                    Ok(competition) => competition.name,
+                    Err(_) => solve.competition_id.mention().to_string(),
+                };
+
+                entry.insert(name);
+            }
+        }
+
+        (points, rank, is_verified, solves, achievements, competition_names)
+    };
+
+    let category_counts: Vec<(ChallengeType, usize)> = ChallengeType::iter()
+        .map(|category| (category, solves.iter().filter(|solve| solve.category == category).count()))
+        .collect();
+
+    let favorite_category = category_counts.iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map(|(category, _)| category.to_string())
+        .unwrap_or_else(|| "None yet".to_string());
+
+    let new_page = |title: &str| CreateEmbed::new()
+        .title(format!("{} - {title}", user.name))
+        .thumbnail(user.face())
+        .color(0xc22026);
+
+    let mut overview = new_page("Profile")
+        .field("Points", points_to_string(points), true)
+        .field("Rank", rank.rank_name().unwrap_or("Unranked"), true)
+        .field("Verified", if is_verified { "Yes" } else { "No" }, true)
+        .field("Total Solves", solves.len().to_string(), true)
+        .field("Favorite Category", favorite_category, true)
+        .field("Achievements", achievements.len().to_string(), true);
+
+    for (category, count) in category_counts.iter() {
+        overview = overview.field(category.to_string(), count.to_string(), true);
+    }
+
+    let mut pages = vec![overview];
+
+    for achievements in achievements.chunks(PROFILE_PAGE_LENGTH) {
+        let mut description = String::new();
+        for achievement in achievements {
+            description.push_str(&format!(
+                "🏆 {} {}\n",
+                achievement.achievement,
+                FormattedTimestamp::new(achievement.earned_at, Some(FormattedTimestampStyle::ShortDate)),
+            ));
+        }
+
+        pages.push(new_page("Achievements").description(description));
+    }
+
+    // competitions in the order of their most recent solve
+    let mut competition_order: Vec<ChannelId> = Vec::new();
+    let mut competition_solves: HashMap<ChannelId, usize> = HashMap::new();
+    for solve in solves.iter() {
+        let count = competition_solves.entry(solve.competition_id).or_default();
+        if *count == 0 {
+            competition_order.push(solve.competition_id);
+        }
+        *count += 1;
+    }
+
+    for competitions in competition_order.chunks(PROFILE_PAGE_LENGTH) {
+        let mut description = String::new();
+        for competition_id in competitions {
+            description.push_str(&format!(
+                "**{}** - {} solves\n",
+                competition_names[competition_id],
+                competition_solves[competition_id],
+            ));
+        }
+
+        pages.push(new_page("Solves per Competition").description(description));
+    }
+
+    for recent_solves in solves.chunks(PROFILE_PAGE_LENGTH) {
+        let mut description = String::new();
+        for solve in recent_solves {
+            match solve.channel_id {
+                Some(channel_id) => description.push_str(&format!("{}/{} in {}\n", solve.category, solve.name, channel_id.mention())),
+                None => description.push_str(&format!("{}/{}\n", solve.category, solve.name)),
+            }
+        }
+
+        pages.push(new_page("Recent Solves").description(description));
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum ChartKind {
+    #[name = "points over time"]
+    Points,
+    #[name = "solves by category"]
+    Categories,
+    #[name = "team solves per competition this semester"]
+    Competitions,
+}
+
+/// Data loaded for each kind of chart, drawn after the database connection is released
+enum ChartData {
+    /// Times and point totals
+    Points(Vec<(i64, i64)>),
+    /// Categories and solve counts
+    Categories(Vec<(String, i64)>),
+    /// Semester of the chart, and competition names and team solve counts
+    Competitions(Semester, Vec<(String, i64)>),
+}
+
+/// Draws a chart of your points or solves, or the team's solves this semester
+#[poise::command(slash_command)]
+pub async fn chart(
+    ctx: CmdContext<'_>,
+    #[description = "Chart to draw"] kind: ChartKind,
+    #[description = "User to draw the chart for, defaults to you"] user: Option<UserId>,
+) -> Result<(), Error> {
+    let user_id = user.unwrap_or(ctx.author().id);
+
+    // the connection is dropped before waiting on discord and drawing the chart
+    let data = {
+        let mut conn = ctx.data().conn().await;
+
+        match kind {
+            ChartKind::Points => {
+                let mut transactions = conn.get_point_transactions_for_user(user_id, u32::MAX).await?;
+                // transactions are newest first
+                transactions.reverse();
+
+                // legacy points have no real date, so they are only counted in the starting total
+                let mut total = 0;
+                ChartData::Points(transactions.iter()
+                    .filter_map(|transaction| {
+                        total += transaction.delta;
+                        (transaction.reason != PointsReason::Legacy).then_some((transaction.created_at.unix_timestamp(), total))
+                    })
+                    .collect())
+            },
+            ChartKind::Categories => {
+                let solves = conn.get_solved_challenges_for_user(user_id).await?;
+                ChartData::Categories(ChallengeType::iter()
+                    .map(|category| {
+                        let count = solves.iter().filter(|solve| solve.category == category).count();
+                        (category.to_string(), count as i64)
+                    })
+                    .collect())
+            },
+            ChartKind::Competitions => {
+                let semester = Semester::current();
+                let semester_start = semester.start();
+
+                let mut history = conn.get_competition_history().await?;
+                history.retain(|competition| competition.created_at >= semester_start);
+                // history is newest first
+                history.reverse();
+
+                ChartData::Competitions(semester, history.into_iter()
+                    .map(|competition| (competition.name, competition.solve_count))
+                    .collect())
+            },
+        }
+    };
+
+    let chart = match data {
+        ChartData::Points(points) => {
+            let user = user_id.to_user(ctx).await?;
+            line_chart(&format!("Points over time for {}", user.name), &points, points_to_string)?
+        },
+        ChartData::Categories(bars) => {
+            let user = user_id.to_user(ctx).await?;
+            bar_chart(&format!("Solves by category for {}", user.name), &bars, |count| count.to_string())?
+        },
+        ChartData::Competitions(semester, bars) => {
+            bar_chart(&format!("Team solves per competition in {semester}"), &bars, |count| count.to_string())?
+        },
+    };
+
+    ctx.send(CreateReply::default().attachment(CreateAttachment::bytes(chart, "chart.png"))).await?;
+
+    Ok(())
+}
+
+/// Shows statistics about the whole server, some are only shown to officers
+#[poise::command(slash_command)]
+pub async fn server(ctx: CmdContext<'_>) -> Result<(), Error> {
+    let show_sensitive = has_perms(&ctx).await;
+    let embed = server_stats_embed(&mut ctx.data().conn().await, show_sensitive).await?;
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(show_sensitive)).await?;
+
+    Ok(())
+}
+
+/// Builds the `/stats server` embed, member counts and approval times are left out unless `show_sensitive` is set
+pub async fn server_stats_embed(conn: &mut DbConn<'_>, show_sensitive: bool) -> Result<CreateEmbed, Error> {
+    let now = Timestamp::now();
+    let month_start: Timestamp = Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .expect("first day of month is a valid date")
+        .into();
+
+    let stats = conn.get_server_stats(month_start.unix_timestamp(), LISTED_CHALLENGE_COUNT).await?;
+
+    let mut embed = CreateEmbed::new()
+        .title("b01lers Server Stats")
+        .color(0xc22026)
+        .timestamp(now);
+
+    if show_sensitive {
+        let approval_time = match (stats.mean_approval_latency(), stats.median_approval_latency()) {
+            (Some(mean), Some(median)) => format!("{} mean, {} median", duration_to_string(mean), duration_to_string(median)),
+            _ => "No approval times recorded".to_string(),
+        };
+
+        embed = embed
+            .field("Verified Members", stats.verified_count.to_string(), true)
+            .field("Active This Month", stats.active_count.to_string(), true)
+            .field("Solve Approval Time", approval_time, true);
+    } else {
+        embed = embed.footer(CreateEmbedFooter::new("Member counts and approval times are only shown to officers"));
+    }
+
+    let solves_by_category = if stats.solves_by_category.is_empty() {
+        "No solves yet".to_string()
+    } else {
+        stats.solves_by_category.iter()
+            .map(|(category, count)| format!("{category}: {count}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let challenges_by_activity = if stats.challenges_by_activity.is_empty() {
+        "No challenge activity yet".to_string()
+    } else {
+        stats.challenges_by_activity.iter()
+            .enumerate()
+            .map(|(i, challenge)| match challenge.channel_id {
+                Some(channel_id) => format!("{}. {} ({})", i + 1, channel_id.mention(), challenge.activity),
+                None => format!("{}. {} ({})", i + 1, challenge.name, challenge.activity),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let total_solves: i64 = stats.solves_by_category.iter().map(|(_, count)| count).sum();
+
+    Ok(embed
+        .field("Competitions Played", stats.competition_count.to_string(), true)
+        .field("Total Solves", total_solves.to_string(), true)
+        .field("Solves by Category", solves_by_category, false)
+        .field("Challenges with the Most Activity (notes, solves, and writeups)", challenges_by_activity, false))
+}
+
+/// Formats a number of seconds like `1d 4h`, `3h 12m`, or `45s`
+fn duration_to_string(seconds: i64) -> String {
+    let days = seconds / SECONDS_PER_DAY;
+    let hours = seconds % SECONDS_PER_DAY / 3600;
+    let minutes = seconds % 3600 / 60;
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{seconds}s")
+    }
+}
@@ -0,0 +1,65 @@
+//! Limits discord puts on a single message, and splitting files across messages to stay under them
+
+use poise::CreateReply;
+use serenity::all::CreateAttachment;
+
+use super::{CmdContext, Error};
+
+/// Max length of an embed description
+pub const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+
+/// Max number of files discord allows on a single message
+pub const MAX_MESSAGE_ATTACHMENTS: usize = 10;
+
+/// Max total size of the files on a single message in a server without boosts
+pub const MAX_MESSAGE_UPLOAD_SIZE: usize = 10 * 1024 * 1024;
+
+/// Sends the reply with as many of the files as fit on it, then the rest of the files in follow up messages
+///
+/// Files too big to upload on their own are left out, and listed in a final message.
+pub async fn send_with_files(ctx: &CmdContext<'_>, reply: CreateReply, files: Vec<CreateAttachment>) -> Result<(), Error> {
+    let mut batches: Vec<Vec<CreateAttachment>> = vec![Vec::new()];
+    let mut batch_size = 0;
+    let mut too_big = Vec::new();
+
+    for file in files {
+        if file.data.len() > MAX_MESSAGE_UPLOAD_SIZE {
+            too_big.push(file.filename);
+            continue;
+        }
+
+        let batch = batches.last_mut().expect("there is always a batch");
+        if batch.len() == MAX_MESSAGE_ATTACHMENTS || batch_size + file.data.len() > MAX_MESSAGE_UPLOAD_SIZE {
+            batch_size = 0;
+            batches.push(Vec::new());
+        }
+
+        batch_size += file.data.len();
+        batches.last_mut().expect("there is always a batch").push(file);
+    }
+
+    let mut batches = batches.into_iter();
+    let mut reply = reply;
+    for file in batches.next().unwrap_or_default() {
+        reply = reply.attachment(file);
+    }
+    ctx.send(reply).await?;
+
+    for batch in batches {
+        let mut follow_up = CreateReply::default();
+        for file in batch {
+            follow_up = follow_up.attachment(file);
+        }
+        ctx.send(follow_up).await?;
+    }
+
+    if !too_big.is_empty() {
+        ctx.say(format!(
+            "Could not upload {}, files can be at most {} MiB",
+            too_big.join(", "),
+            MAX_MESSAGE_UPLOAD_SIZE / 1024 / 1024,
+        )).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use poise::CreateReply;
+use serenity::all::{ChannelId, ComponentInteraction, Context, CreateEmbed, CreateMessage, Mentionable, UserId};
+
+use crate::config::config;
+use crate::db::{ApprovalStatus, ChallengeType, PointsReason, Writeup};
+use crate::points::give_points;
+
+use super::solve::{approval_buttons, close_approval_message};
+use super::{CmdContext, CommandContext, Error, competition::{get_competition_from_ctx, get_challenge_from_ctx}};
+
+/// Max number of writeups shown by `/writeup list`
+const WRITEUP_LIST_LIMIT: usize = 20;
+
+#[poise::command(slash_command, subcommands("add", "list"))]
+pub async fn writeup(_ctx: CmdContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Adds a writeup for the current channel's challenge
+#[poise::command(slash_command)]
+pub async fn add(
+    ctx: CmdContext<'_>,
+    #[description = "Link to the writeup"] url: String,
+) -> Result<(), Error> {
+    let competition = get_competition_from_ctx(&ctx).await?;
+    let challenge = get_challenge_from_ctx(&ctx).await?;
+
+    if reqwest::Url::parse(&url).is_err() {
+        return Err(anyhow::anyhow!("Invalid writeup url"));
+    }
+
+    let author_id = ctx.author().id;
+
+    // only send writeup for approval if it is worth bonus points
+    let approval_message_id = match config().ranks.points_per_writeup {
+        Some(_) => {
+            let approval_embed = CreateEmbed::new()
+                .title("New Writeup")
+                .description(format!("Here is a new writeup submitted by {} for bonus points", author_id.mention()))
+                .color(0xc22026)
+                .field("Challenge", &challenge.name, true)
+                .field("Category", challenge.category.to_string(), true)
+                .field("CTF", competition.channel_id.mention().to_string(), true)
+                .field("Writeup", &url, false);
+
+            let approval_message = CreateMessage::new()
+                .add_embed(approval_embed)
+                .components(approval_buttons("writeup_accept", "writeup_reject"));
+
+            let approval_message = config().server.solve_approvals_channel_id
+                .send_message(ctx, approval_message).await?;
+
+            Some(approval_message.id)
+        },
+        None => None,
+    };
+
+    let writeup = Writeup {
+        id: 0,
+        challenge_id: challenge.id,
+        user_id: author_id,
+        url,
+        approval_message_id,
+        approval_status: ApprovalStatus::Pending,
+    };
+
+    let mut conn = ctx.data().conn().await;
+    conn.create_writeup(writeup).await?;
+    conn.commit().await?;
+
+    ctx.say(format!("Your writeup for {} has been recorded.", challenge.name)).await?;
+
+    Ok(())
+}
+
+/// Lists writeups written by b01lers members
+#[poise::command(slash_command)]
+pub async fn list(
+    ctx: CmdContext<'_>,
+    #[description = "Only list writeups from this competition"] competition: Option<ChannelId>,
+    #[description = "Only list writeups for this category"] category: Option<ChallengeType>,
+    #[description = "Only list writeups by this user"] user: Option<UserId>,
+) -> Result<(), Error> {
+    let mut conn = ctx.data().conn().await;
+
+    let writeups = conn.get_writeups(competition, category, user).await?;
+
+    let mut description = String::new();
+    for writeup in writeups.iter().take(WRITEUP_LIST_LIMIT) {
+        let challenge = conn.get_challenge_by_id(writeup.challenge_id).await?;
+
+        description.push_str(&format!(
+            "[{}/{}]({}) by {} in {}\n",
+            challenge.category,
+            challenge.name,
+            writeup.url,
+            writeup.user_id.mention(),
+            challenge.competition_id.mention(),
+        ));
+    }
+
+    if writeups.len() > WRITEUP_LIST_LIMIT {
+        description.push_str(&format!("...and {} more", writeups.len() - WRITEUP_LIST_LIMIT));
+    }
+
+    if description.is_empty() {
+        description.push_str("No writeups found");
+    }
+
+    let writeups_embed = CreateEmbed::new()
+        .title("Writeups")
+        .description(description)
+        .color(0xc22026);
+
+    ctx.send(CreateReply::default().embed(writeups_embed)).await?;
+
+    Ok(())
+}
+
+/// Recieves writeup approval button presses and gives the author bonus points if approved
+pub async fn handle_approval_button(context: &Context, cmd_context: &CommandContext, interaction: &ComponentInteraction) -> anyhow::Result<()> {
+    let mut conn = cmd_context.conn().await;
+
+    let mut message = interaction.message.clone();
+    let mut writeup = conn.get_writeup_by_approval_message_id(message.id).await?;
+
+    if writeup.approval_status != ApprovalStatus::Pending {
+        message.reply(context, format!("writeup is alredy {}", writeup.approval_status)).await?;
+    } else if interaction.data.custom_id == "writeup_accept" {
+        writeup.approval_status = ApprovalStatus::Approved;
+
+        let points = config().ranks.points_per_writeup.unwrap_or_default();
+        give_points(
+            context,
+            &mut conn,
+            writeup.user_id,
+            points,
+            &PointsReason::Writeup { writeup_id: writeup.id },
+            Some(interaction.user.id),
+        ).await?;
+
+        close_approval_message(context, &mut message, writeup.approval_status, interaction.user.id).await?;
+    } else if interaction.data.custom_id == "writeup_reject" {
+        writeup.approval_status = ApprovalStatus::Declined;
+
+        close_approval_message(context, &mut message, writeup.approval_status, interaction.user.id).await?;
+    }
+
+    conn.update_writeup(writeup).await?;
+
+    conn.commit().await?;
+
+    // acknowledge interaction
+    interaction.defer(context).await?;
+
+    Ok(())
+}
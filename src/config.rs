@@ -1,14 +1,24 @@
-use std::{path::Path, sync::OnceLock};
+use std::{collections::{BTreeMap, HashMap}, path::Path, sync::OnceLock};
 
 use tokio::fs::read_to_string;
 use serde::{Serialize, Deserialize};
 use serenity::all::{ChannelId, EmojiId, GuildId};
 
+use crate::bingo::normalize_label;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub mailgun: MailgunConfig,
     pub server: ServerConfig,
     pub ranks: RankConfig,
+    pub message_points: MessagePointsConfig,
+    pub participation_points: ParticipationPointsConfig,
+    #[serde(default)]
+    pub achievement_points: AchievementPointsConfig,
+    pub bingo: BingoConfig,
+    /// Weekly server stats digest, not posted if the section is left out
+    #[serde(default)]
+    pub stats_digest: Option<StatsDigestConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,20 +48,273 @@ pub struct ServerConfig {
 pub struct RankConfig {
     pub points_per_solve: i64,
     pub points_per_message: i64,
+    /// Bonus points given for a writeup once an officer approves it, writeups are not sent for approval if unset
+    #[serde(default)]
+    pub points_per_writeup: Option<i64>,
     pub rank_names: Vec<String>,
+    /// Whether people can lose their rank when the cutoffs rise
+    #[serde(default)]
+    pub rank_policy: RankPolicy,
+    /// How the points needed for each rank are calculated
+    #[serde(default)]
+    pub cutoffs: CutoffMode,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CutoffMode {
+    /// The top rank needs the highest score, and each rank below needs `ratio` times the points of the rank above
+    Geometric { ratio: f64 },
+    /// Ranks are evenly spaced between 0 and the highest score
+    Linear,
+    /// Each rank name needs a fixed number of points
+    Absolute { thresholds: HashMap<String, i64> },
+    /// Ranks are evenly split between users who earned points in the last `active_days` days
+    Percentile { active_days: i64 },
+}
+
+impl Default for CutoffMode {
+    fn default() -> Self {
+        CutoffMode::Geometric { ratio: 0.75 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankPolicy {
+    /// Ranks are only lost when points are lost
+    #[default]
+    Sticky,
+    /// Ranks always match the current cutoffs, so they are lost when other people raise the cutoffs
+    Recomputed,
 }
 
 impl RankConfig {
     pub fn rank_count(&self) -> usize {
         self.rank_names.len()
     }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match &self.cutoffs {
+            // a ratio of 1 or more would give lower ranks the same or more points than higher ranks
+            CutoffMode::Geometric { ratio } if !(*ratio > 0.0 && *ratio < 1.0) => {
+                anyhow::bail!("geometric cutoff ratio must be between 0 and 1, but is {ratio}");
+            },
+            CutoffMode::Percentile { active_days } if *active_days <= 0 => {
+                anyhow::bail!("percentile cutoff active_days must be more than 0, but is {active_days}");
+            },
+            _ => (),
+        }
+
+        if let CutoffMode::Absolute { thresholds } = &self.cutoffs {
+            for rank_name in self.rank_names.iter() {
+                if !thresholds.contains_key(rank_name) {
+                    anyhow::bail!("no point threshold configured for rank {rank_name}");
+                }
+            }
+
+            for name in thresholds.keys() {
+                if !self.rank_names.contains(name) {
+                    anyhow::bail!("point threshold configured for rank {name}, which is not in rank_names");
+                }
+            }
+
+            // ranks are found by searching the thresholds, so a higher rank can't need fewer points
+            let ordered = self.rank_names.windows(2)
+                .all(|pair| thresholds[&pair[0]] <= thresholds[&pair[1]]);
+            if !ordered {
+                anyhow::bail!("point thresholds must not decrease from the lowest rank to the highest rank");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rules to stop people from farming points by spamming messages
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessagePointsConfig {
+    /// Seconds after earning message points before a user can earn them again
+    pub cooldown_seconds: i64,
+    /// Messages shorter than this many characters are not worth points
+    pub min_length: usize,
+    /// Messages in these channels, or channels under these categories, are not worth points
+    pub ignored_channels: Vec<ChannelId>,
+    /// Max points a user can earn from messages in a day
+    pub daily_cap: i64,
+    /// How often message point limits are saved to the database
+    pub persist_interval_seconds: u64,
+    /// How often earned message points are given out
+    pub flush_interval_seconds: u64,
+    /// Number of messages worth points after which earned message points are given out early
+    pub flush_threshold: usize,
+    /// Multipliers for messages in a channel, or in channels under a category, like 0.5 for off-topic
+    #[serde(default)]
+    pub channel_multipliers: HashMap<ChannelId, f64>,
+    /// Multiplier for messages in challenge threads of competitions which haven't been archived
+    #[serde(default = "default_multiplier")]
+    pub challenge_multiplier: f64,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+/// Points for participating outside of text chat, a source is disabled if its points are left out
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParticipationPointsConfig {
+    /// Voice channels where time spent while a competition is running is worth points
+    pub voice_channels: Vec<ChannelId>,
+    #[serde(default)]
+    pub points_per_voice_minute: Option<i64>,
+    /// Max points a user can earn from voice in a day
+    pub voice_daily_cap: i64,
+    /// Points given to the author of a message for each person who reacts to it
+    #[serde(default)]
+    pub points_per_reaction: Option<i64>,
+    /// Points for checking in to a meeting
+    #[serde(default)]
+    pub points_per_attendance: Option<i64>,
+    /// Minutes after opening meeting check in that the code can be used
+    pub attendance_code_minutes: i64,
+}
+
+/// Bonus points for earning achievements, an achievement is not worth points if it is left out
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AchievementPointsConfig {
+    #[serde(default)]
+    pub first_category_solve: Option<i64>,
+    #[serde(default)]
+    pub first_blood: Option<i64>,
+    /// Points for each of 10, 50, and 100 solves
+    #[serde(default)]
+    pub solve_milestone: Option<i64>,
+    /// Points for solving a challenge in every category of a competition
+    #[serde(default)]
+    pub all_categories: Option<i64>,
+    /// Points for playing in a competition which completed a bad ctf bingo line
+    #[serde(default)]
+    pub bingo_line: Option<i64>,
+}
+
+/// Largest number of squares on a side of a bingo board, marks are saved as bits of a 64 bit integer
+pub const MAX_BINGO_BOARD_SIZE: usize = 8;
+/// Most bonus squares a bingo board can have, their marks are saved as bits of a separate 64 bit integer
+pub const MAX_BINGO_BONUS_SQUARES: usize = 64;
+/// Longest name a bingo board can have, it is part of the square buttons' custom ids which discord limits to 100 characters
+pub const MAX_BINGO_BOARD_NAME_LENGTH: usize = 50;
+/// Longest label a bingo square can have, labels are autocomplete choices which discord limits to 100 characters
+pub const MAX_BINGO_LABEL_LENGTH: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BingoConfig {
+    /// Board used by competitions which haven't picked one
+    pub default_board: String,
+    pub boards: BTreeMap<String, BingoBoardConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BingoBoardConfig {
+    /// Drawn above the board
+    pub title: String,
+    /// Number of squares on each side of the board
+    pub size: usize,
+    /// Labels of every square, row by row starting at the top left
+    ///
+    /// Marks are saved by the position of the square, so reordering squares moves existing marks.
+    pub squares: Vec<String>,
+    /// Label of a square which is always marked
+    #[serde(default)]
+    pub free_square: Option<String>,
+    /// Squares shown under the board which don't count towards lines
+    #[serde(default)]
+    pub bonus_squares: Vec<String>,
+}
+
+impl BingoConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if !self.boards.contains_key(&self.default_board) {
+            anyhow::bail!("default bingo board {} does not exist", self.default_board);
+        }
+
+        for (name, board) in self.boards.iter() {
+            if name.chars().count() > MAX_BINGO_BOARD_NAME_LENGTH {
+                anyhow::bail!("bingo board name {name} is longer than {MAX_BINGO_BOARD_NAME_LENGTH} characters");
+            }
+
+            if board.size == 0 || board.size > MAX_BINGO_BOARD_SIZE {
+                anyhow::bail!("bingo board {name} must have a size between 1 and {MAX_BINGO_BOARD_SIZE}");
+            }
+
+            if board.squares.len() != board.size * board.size {
+                anyhow::bail!("bingo board {name} needs {} squares, but has {}", board.size * board.size, board.squares.len());
+            }
+
+            if board.bonus_squares.len() > MAX_BINGO_BONUS_SQUARES {
+                anyhow::bail!("bingo board {name} can have at most {MAX_BINGO_BONUS_SQUARES} bonus squares");
+            }
+
+            let labels = board.squares.iter().chain(board.bonus_squares.iter());
+            if let Some(label) = labels.clone().find(|label| label.chars().count() > MAX_BINGO_LABEL_LENGTH) {
+                anyhow::bail!("bingo board {name} has a square label longer than {MAX_BINGO_LABEL_LENGTH} characters: {label}");
+            }
+
+            // squares are found by their label, so labels can't be shared
+            let mut labels: Vec<String> = labels
+                .map(|label| normalize_label(label))
+                .collect();
+            labels.sort();
+            if let Some(label) = labels.windows(2).find(|pair| pair[0] == pair[1]) {
+                anyhow::bail!("bingo board {name} has more than one square called {}", label[0]);
+            }
+
+            if let Some(free_square) = &board.free_square {
+                if !board.squares.contains(free_square) {
+                    anyhow::bail!("free square {free_square} is not on bingo board {name}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// When and where the weekly server stats digest is posted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatsDigestConfig {
+    /// Channel the digest is posted in, it includes officer only numbers
+    pub channel_id: ChannelId,
+    /// Day of the week the digest is posted, 0 is monday and 6 is sunday
+    pub weekday: u32,
+    /// Hour of the day in utc the digest is posted
+    pub hour: u32,
+}
+
+impl StatsDigestConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.weekday > 6 {
+            anyhow::bail!("stats digest weekday must be between 0 (monday) and 6 (sunday)");
+        }
+
+        if self.hour > 23 {
+            anyhow::bail!("stats digest hour must be between 0 and 23");
+        }
+
+        Ok(())
+    }
 }
 
 static CONFIG: OnceLock<Config> = OnceLock::new();
 
 pub async fn load_config(path: &Path) -> anyhow::Result<()> {
     let config_data = read_to_string(path).await?;
-    let config = toml::from_str(&config_data)?;
+    let config: Config = toml::from_str(&config_data)?;
+    config.ranks.validate()?;
+    config.bingo.validate()?;
+    if let Some(stats_digest) = &config.stats_digest {
+        stats_digest.validate()?;
+    }
+
     CONFIG.set(config)
         .or_else(|_| Err(anyhow::anyhow!("config already loaded")))?;
 
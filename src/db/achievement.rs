@@ -0,0 +1,85 @@
+use serenity::all::{ChannelId, Mentionable, Timestamp, UserId};
+
+use super::ChallengeType;
+
+#[derive(Debug, Clone)]
+pub struct UserAchievementRaw {
+    pub id: i64,
+    pub user_id: i64,
+    pub achievement: i64,
+    pub reference_id: i64,
+    pub earned_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    /// First approved solve in a category
+    FirstCategorySolve { category: ChallengeType },
+    /// Part of the first approved solve in a competition
+    FirstBlood { competition_id: ChannelId },
+    /// Solved a milestone number of distinct challenges, like 10 or 50
+    SolveMilestone { solves: i64 },
+    /// Solved a challenge in every category of a competition
+    AllCategories { competition_id: ChannelId },
+    /// Played in a competition which completed a bad ctf bingo line
+    BingoLine { competition_id: ChannelId },
+}
+
+impl Achievement {
+    /// Splits the achievement into the `achievement` and `reference_id` columns
+    pub fn to_columns(self) -> (i64, i64) {
+        match self {
+            Self::FirstCategorySolve { category } => (0, category as i64),
+            Self::FirstBlood { competition_id } => (1, competition_id.get() as i64),
+            Self::SolveMilestone { solves } => (2, solves),
+            Self::AllCategories { competition_id } => (3, competition_id.get() as i64),
+            Self::BingoLine { competition_id } => (4, competition_id.get() as i64),
+        }
+    }
+
+    fn from_columns(achievement: i64, reference_id: i64) -> Self {
+        match achievement {
+            0 => Self::FirstCategorySolve {
+                category: ChallengeType::from_repr(reference_id).expect("invalid category returned from database"),
+            },
+            1 => Self::FirstBlood { competition_id: ChannelId::new(reference_id as u64) },
+            2 => Self::SolveMilestone { solves: reference_id },
+            3 => Self::AllCategories { competition_id: ChannelId::new(reference_id as u64) },
+            4 => Self::BingoLine { competition_id: ChannelId::new(reference_id as u64) },
+            _ => panic!("invalid achievement returned from database"),
+        }
+    }
+}
+
+impl std::fmt::Display for Achievement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FirstCategorySolve { category } => write!(f, "first {category} solve"),
+            Self::FirstBlood { competition_id } => write!(f, "first blood in {}", competition_id.mention()),
+            Self::SolveMilestone { solves } => write!(f, "{solves} challenges solved"),
+            Self::AllCategories { competition_id } => write!(f, "solved every category in {}", competition_id.mention()),
+            Self::BingoLine { competition_id } => write!(f, "bad ctf bingo in {}", competition_id.mention()),
+        }
+    }
+}
+
+/// An achievement earned by a user
+#[derive(Debug, Clone)]
+pub struct UserAchievement {
+    pub id: i64,
+    pub user_id: UserId,
+    pub achievement: Achievement,
+    pub earned_at: Timestamp,
+}
+
+impl From<UserAchievementRaw> for UserAchievement {
+    fn from(value: UserAchievementRaw) -> Self {
+        UserAchievement {
+            id: value.id,
+            user_id: UserId::new(value.user_id as u64),
+            achievement: Achievement::from_columns(value.achievement, value.reference_id),
+            earned_at: Timestamp::from_unix_timestamp(value.earned_at)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
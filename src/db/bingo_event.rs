@@ -0,0 +1,48 @@
+use serenity::all::{Timestamp, UserId};
+
+#[derive(Debug, Clone)]
+pub struct BingoEventRaw {
+    pub board: String,
+    pub square: String,
+    pub marked: bool,
+    pub board_changed: bool,
+    pub user_id: i64,
+    pub created_at: i64,
+}
+
+/// What happened to a competition's bingo board
+#[derive(Debug, Clone)]
+pub enum BingoChange {
+    /// A square was marked or unmarked, `marked` is `false` if it was unmarked
+    Square { square: String, marked: bool },
+    /// The competition switched boards, clearing every mark
+    Board,
+}
+
+/// A bingo square being marked or unmarked, or the board being changed, in a competition
+#[derive(Debug, Clone)]
+pub struct BingoEvent {
+    /// Name of the bingo board in config the competition was using, or switched to
+    pub board: String,
+    pub change: BingoChange,
+    pub user_id: UserId,
+    pub created_at: Timestamp,
+}
+
+impl From<BingoEventRaw> for BingoEvent {
+    fn from(value: BingoEventRaw) -> Self {
+        let change = if value.board_changed {
+            BingoChange::Board
+        } else {
+            BingoChange::Square { square: value.square, marked: value.marked }
+        };
+
+        BingoEvent {
+            board: value.board,
+            change,
+            user_id: UserId::new(value.user_id as u64),
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
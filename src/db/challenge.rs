@@ -1,4 +1,4 @@
-use serenity::all::ChannelId;
+use serenity::all::{ChannelId, MessageId, Timestamp};
 use poise::macros::ChoiceParameter;
 use strum::FromRepr;
 
@@ -9,6 +9,8 @@ pub struct ChallengeRaw {
     pub name: String,
     pub category: i64,
     pub channel_id: Option<i64>,
+    pub notes_message_id: Option<i64>,
+    pub created_at: i64,
 }
 
 impl From<Challenge> for ChallengeRaw {
@@ -19,6 +21,8 @@ impl From<Challenge> for ChallengeRaw {
             name: value.name,
             category: value.category as i64,
             channel_id: value.channel_id.map(|id| id.get() as i64),
+            notes_message_id: value.notes_message_id.map(|id| id.get() as i64),
+            created_at: value.created_at.unix_timestamp(),
         }
     }
 }
@@ -68,6 +72,9 @@ pub struct Challenge {
     pub name: String,
     pub category: ChallengeType,
     pub channel_id: Option<ChannelId>,
+    /// Pinned message with a digest of the challenge notes
+    pub notes_message_id: Option<MessageId>,
+    pub created_at: Timestamp,
 }
 
 impl From<ChallengeRaw> for Challenge {
@@ -79,6 +86,9 @@ impl From<ChallengeRaw> for Challenge {
             category: ChallengeType::from_repr(value.category)
                 .expect("invalid challenge category returned from database"),
             channel_id: value.channel_id.map(|id| ChannelId::new(id as u64)),
+            notes_message_id: value.notes_message_id.map(|id| MessageId::new(id as u64)),
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
         }
     }
 }
\ No newline at end of file
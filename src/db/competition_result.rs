@@ -0,0 +1,112 @@
+use serenity::all::{ChannelId, MessageId, Timestamp};
+
+#[derive(Debug, Clone)]
+pub struct CompetitionResultRaw {
+    pub competition_id: i64,
+    pub place: i64,
+    pub team_count: i64,
+    pub points: i64,
+    pub scoreboard_channel_id: Option<i64>,
+    pub scoreboard_message_id: Option<i64>,
+}
+
+impl From<CompetitionResult> for CompetitionResultRaw {
+    fn from(value: CompetitionResult) -> Self {
+        CompetitionResultRaw {
+            competition_id: value.competition_id.get() as i64,
+            place: value.place,
+            team_count: value.team_count,
+            points: value.points,
+            scoreboard_channel_id: value.scoreboard_message.map(|(channel_id, _)| channel_id.get() as i64),
+            scoreboard_message_id: value.scoreboard_message.map(|(_, message_id)| message_id.get() as i64),
+        }
+    }
+}
+
+/// How b01lers placed in a competition
+#[derive(Debug, Clone)]
+pub struct CompetitionResult {
+    pub competition_id: ChannelId,
+    pub place: i64,
+    pub team_count: i64,
+    pub points: i64,
+    /// Channel and id of the message the scoreboard screenshot is attached to
+    pub scoreboard_message: Option<(ChannelId, MessageId)>,
+}
+
+impl CompetitionResult {
+    /// Formats the placement like `3rd / 1024`
+    pub fn placement_string(&self) -> String {
+        let suffix = match (self.place % 10, self.place % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+
+        format!("{}{suffix} / {}", self.place, self.team_count)
+    }
+}
+
+impl From<CompetitionResultRaw> for CompetitionResult {
+    fn from(value: CompetitionResultRaw) -> Self {
+        CompetitionResult {
+            competition_id: ChannelId::new(value.competition_id as u64),
+            place: value.place,
+            team_count: value.team_count,
+            points: value.points,
+            scoreboard_message: value.scoreboard_channel_id.zip(value.scoreboard_message_id)
+                .map(|(channel_id, message_id)| (ChannelId::new(channel_id as u64), MessageId::new(message_id as u64))),
+        }
+    }
+}
+
+pub struct CompetitionHistoryRaw {
+    pub channel_id: i64,
+    pub name: String,
+    pub created_at: i64,
+    pub place: Option<i64>,
+    pub team_count: Option<i64>,
+    pub points: Option<i64>,
+    pub solve_count: i64,
+    pub participant_count: i64,
+}
+
+/// Summary of how b01lers did in a past competition
+#[derive(Debug, Clone)]
+pub struct CompetitionHistory {
+    pub name: String,
+    pub created_at: Timestamp,
+    pub result: Option<CompetitionResult>,
+    /// Number of challenges with an approved solve
+    pub solve_count: i64,
+    /// Number of users who were part of an approved solve
+    pub participant_count: i64,
+}
+
+impl From<CompetitionHistoryRaw> for CompetitionHistory {
+    fn from(value: CompetitionHistoryRaw) -> Self {
+        let channel_id = ChannelId::new(value.channel_id as u64);
+
+        let result = match (value.place, value.team_count, value.points) {
+            (Some(place), Some(team_count), Some(points)) => Some(CompetitionResult {
+                competition_id: channel_id,
+                place,
+                team_count,
+                points,
+                scoreboard_message: None,
+            }),
+            _ => None,
+        };
+
+        CompetitionHistory {
+            name: value.name,
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
+            result,
+            solve_count: value.solve_count,
+            participant_count: value.participant_count,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use serenity::all::Timestamp;
+
+#[derive(Debug, Clone)]
+pub struct MeetingRaw {
+    pub id: i64,
+    pub name: String,
+    pub code: String,
+    pub closes_at: i64,
+}
+
+/// A meeting members can check in to for attendance points
+#[derive(Debug, Clone)]
+pub struct Meeting {
+    pub id: i64,
+    pub name: String,
+    /// Code members enter to check in
+    pub code: String,
+    /// When the code stops working
+    pub closes_at: Timestamp,
+}
+
+impl From<MeetingRaw> for Meeting {
+    fn from(value: MeetingRaw) -> Self {
+        Meeting {
+            id: value.id,
+            name: value.name,
+            code: value.code,
+            closes_at: Timestamp::from_unix_timestamp(value.closes_at)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
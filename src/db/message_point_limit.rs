@@ -0,0 +1,42 @@
+use serenity::all::UserId;
+
+#[derive(Debug, Clone)]
+pub struct MessagePointLimitRaw {
+    pub user_id: i64,
+    pub day: i64,
+    pub points_today: i64,
+    pub last_awarded_at: i64,
+}
+
+impl From<MessagePointLimit> for MessagePointLimitRaw {
+    fn from(value: MessagePointLimit) -> Self {
+        MessagePointLimitRaw {
+            user_id: value.user_id.get() as i64,
+            day: value.day,
+            points_today: value.points_today,
+            last_awarded_at: value.last_awarded_at,
+        }
+    }
+}
+
+/// Tracks how many points a user has earned from messages, to limit point farming
+#[derive(Debug, Clone)]
+pub struct MessagePointLimit {
+    pub user_id: UserId,
+    /// Day that `points_today` was counted on, as days since the unix epoch in UTC
+    pub day: i64,
+    pub points_today: i64,
+    /// Unix timestamp of the last message that earned points
+    pub last_awarded_at: i64,
+}
+
+impl From<MessagePointLimitRaw> for MessagePointLimit {
+    fn from(value: MessagePointLimitRaw) -> Self {
+        MessagePointLimit {
+            user_id: UserId::new(value.user_id as u64),
+            day: value.day,
+            points_today: value.points_today,
+            last_awarded_at: value.last_awarded_at,
+        }
+    }
+}
@@ -1,15 +1,42 @@
+use std::future::Future;
+use std::pin::Pin;
+
 use serenity::all::{ChannelId, MessageId, UserId};
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteConnection, Sqlite};
 use sqlx::Transaction;
 
-pub use competition::{Competition, BingoSquare};
+pub use competition::Competition;
 pub use user::User;
 pub use challenge::{Challenge, ChallengeType};
 pub use solve::{ApprovalStatus, Solve};
+pub use competition_result::{CompetitionHistory, CompetitionResult};
+pub use writeup::Writeup;
+pub use note::{Note, NoteFile};
+pub use point_transaction::{PointTransaction, PointsReason};
+pub use message_point_limit::MessagePointLimit;
+pub use season::{Season, SeasonStanding};
+pub use meeting::Meeting;
+pub use point_multiplier::PointMultiplier;
+pub use server_stats::{ChallengeActivity, ServerStats};
+pub use achievement::{Achievement, UserAchievement};
+pub use bingo_event::{BingoChange, BingoEvent};
+pub use voice_session::VoiceSession;
 use competition::CompetitionRaw;
+use competition_result::{CompetitionHistoryRaw, CompetitionResultRaw};
 use user::UserRaw;
 use challenge::ChallengeRaw;
 use solve::SolveRaw;
+use writeup::WriteupRaw;
+use note::NoteRaw;
+use point_transaction::PointTransactionRaw;
+use message_point_limit::MessagePointLimitRaw;
+use season::{SeasonRaw, SeasonStandingRaw};
+use meeting::MeetingRaw;
+use point_multiplier::PointMultiplierRaw;
+use server_stats::{CategorySolvesRaw, ChallengeActivityRaw};
+use achievement::UserAchievementRaw;
+use bingo_event::BingoEventRaw;
+use voice_session::VoiceSessionRaw;
 
 use crate::points::Rank;
 
@@ -17,7 +44,20 @@ mod competition;
 mod user;
 mod challenge;
 mod solve;
+mod competition_result;
+mod writeup;
+mod note;
+mod point_transaction;
+mod message_point_limit;
+mod season;
+mod meeting;
+mod point_multiplier;
+mod server_stats;
+mod achievement;
+mod bingo_event;
+mod voice_session;
 
+#[derive(Clone)]
 pub struct DbContext {
     pool: SqlitePool,
 }
@@ -39,6 +79,7 @@ impl DbContext {
     pub async fn try_conn<'pool>(&'pool self) -> Result<DbConn<'pool>, anyhow::Error> {
         Ok(DbConn {
             transaction: self.pool.begin().await?,
+            after_commit: Vec::new(),
         })
     }
 
@@ -47,8 +88,12 @@ impl DbContext {
     }
 }
 
+type AfterCommitTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
 pub struct DbConn<'a> {
     transaction: Transaction<'a, Sqlite>,
+    /// Tasks started once the transaction is committed, dropped if it is rolled back
+    after_commit: Vec<AfterCommitTask>,
 }
 
 impl DbConn<'_> {
@@ -56,8 +101,22 @@ impl DbConn<'_> {
         &mut self.transaction
     }
 
+    /// Runs `task` in the background after this transaction is committed
+    /// 
+    /// Use this for discord side effects like roles and announcements, so they are never done for changes that were rolled back,
+    /// and slow requests don't hold the database lock.
+    pub fn after_commit(&mut self, task: impl Future<Output = ()> + Send + 'static) {
+        self.after_commit.push(Box::pin(task));
+    }
+
     pub async fn commit(self) -> Result<(), anyhow::Error> {
-        Ok(self.transaction.commit().await?)
+        self.transaction.commit().await?;
+
+        for task in self.after_commit {
+            tokio::spawn(task);
+        }
+
+        Ok(())
     }
 
     pub async fn rollback(self) -> Result<(), anyhow::Error> {
@@ -67,10 +126,13 @@ impl DbConn<'_> {
     pub async fn create_competition(&mut self, competition: Competition) -> Result<(), anyhow::Error> {
         let competition_raw: CompetitionRaw = competition.into();
         sqlx::query!(
-            "INSERT INTO competition (channel_id, name, bingo) VALUES (?, ?, ?)",
+            "INSERT INTO competition (channel_id, name, bingo, created_at, bingo_board, bingo_bonus) VALUES (?, ?, ?, ?, ?, ?)",
             competition_raw.channel_id,
             competition_raw.name,
-            competition_raw.bingo
+            competition_raw.bingo,
+            competition_raw.created_at,
+            competition_raw.bingo_board,
+            competition_raw.bingo_bonus,
         )
         .execute(self.connection())
         .await?;
@@ -97,9 +159,11 @@ impl DbConn<'_> {
     pub async fn update_competition(&mut self, competition: Competition) -> Result<(), anyhow::Error> {
         let competition_raw: CompetitionRaw = competition.into();
         sqlx::query!(
-            "UPDATE competition SET name = ?, bingo = ? WHERE channel_id = ?",
+            "UPDATE competition SET name = ?, bingo = ?, bingo_board = ?, bingo_bonus = ? WHERE channel_id = ?",
             competition_raw.name,
             competition_raw.bingo,
+            competition_raw.bingo_board,
+            competition_raw.bingo_bonus,
             competition_raw.channel_id,
         )
         .execute(self.connection())
@@ -108,11 +172,181 @@ impl DbConn<'_> {
         Ok(())
     }
 
+    /// Gets every competition, oldest first
+    pub async fn get_all_competitions(&mut self) -> Result<Vec<Competition>, anyhow::Error> {
+        let competitions = sqlx::query_as!(CompetitionRaw, "SELECT * FROM competition ORDER BY created_at")
+            .map(Competition::from)
+            .fetch_all(self.connection())
+            .await?;
+
+        Ok(competitions)
+    }
+
+    /// Records that a user marked or unmarked a bingo square in a competition
+    pub async fn create_bingo_event(
+        &mut self,
+        competition_id: ChannelId,
+        board: &str,
+        square: &str,
+        marked: bool,
+        user_id: UserId,
+    ) -> Result<(), anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        let user_id = user_id.get() as i64;
+        sqlx::query!(
+            "INSERT INTO bingo_events (competition_id, board, square, marked, user_id, created_at) VALUES (?, ?, ?, ?, ?, unixepoch())",
+            competition_id,
+            board,
+            square,
+            marked,
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Records the competition switching to a different bingo board
+    pub async fn create_bingo_board_event(&mut self, competition_id: ChannelId, board: &str, user_id: UserId) -> Result<(), anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        let user_id = user_id.get() as i64;
+        sqlx::query!(
+            "INSERT INTO bingo_events (competition_id, board, square, marked, board_changed, user_id, created_at)
+            VALUES (?, ?, '', 0, 1, ?, unixepoch())",
+            competition_id,
+            board,
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Gets every time a square was marked or unmarked, or the board was changed, in the competition, oldest first
+    pub async fn get_bingo_events_for_competition(&mut self, competition_id: ChannelId) -> Result<Vec<BingoEvent>, anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        let events = sqlx::query_as!(
+            BingoEventRaw,
+            r#"SELECT board, square, marked AS "marked: bool", board_changed AS "board_changed: bool", user_id, created_at
+            FROM bingo_events WHERE competition_id = ? ORDER BY created_at, id"#,
+            competition_id,
+        ).map(BingoEvent::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(events)
+    }
+
+    /// Records the result of a competition, replacing any previously recorded result
+    pub async fn set_competition_result(&mut self, result: CompetitionResult) -> Result<(), anyhow::Error> {
+        let result_raw: CompetitionResultRaw = result.into();
+        sqlx::query!(
+            "INSERT INTO competition_results (competition_id, place, team_count, points, scoreboard_channel_id, scoreboard_message_id)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(competition_id) DO UPDATE SET
+            place = excluded.place, team_count = excluded.team_count, points = excluded.points,
+            scoreboard_channel_id = excluded.scoreboard_channel_id, scoreboard_message_id = excluded.scoreboard_message_id",
+            result_raw.competition_id,
+            result_raw.place,
+            result_raw.team_count,
+            result_raw.points,
+            result_raw.scoreboard_channel_id,
+            result_raw.scoreboard_message_id,
+        )
+        .execute(self.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the result of a competition, or `None` if no result has been recorded
+    pub async fn get_competition_result(
+        &mut self,
+        competition_id: ChannelId,
+    ) -> Result<Option<CompetitionResult>, anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        let result_raw = sqlx::query_as!(
+            CompetitionResultRaw,
+            "SELECT * FROM competition_results WHERE competition_id = ?",
+            competition_id,
+        )
+        .fetch_optional(self.connection())
+        .await?;
+
+        Ok(result_raw.map(CompetitionResult::from))
+    }
+
+    /// Gets a summary of every competition, newest first
+    pub async fn get_competition_history(&mut self) -> Result<Vec<CompetitionHistory>, anyhow::Error> {
+        let history = sqlx::query_as!(
+            CompetitionHistoryRaw,
+            r#"SELECT competition.channel_id, competition.name, competition.created_at,
+            competition_results.place AS "place?",
+            competition_results.team_count AS "team_count?",
+            competition_results.points AS "points?",
+            (SELECT COUNT(DISTINCT solves.challenge_id) FROM solves
+                INNER JOIN challenges ON solves.challenge_id = challenges.id
+                WHERE challenges.competition_id = competition.channel_id AND solves.approval_status = ?1
+            ) AS "solve_count!: i64",
+            (SELECT COUNT(DISTINCT user_solves.user_id) FROM solves
+                INNER JOIN user_solves ON solves.id = user_solves.solve_id
+                INNER JOIN challenges ON solves.challenge_id = challenges.id
+                WHERE challenges.competition_id = competition.channel_id AND solves.approval_status = ?1
+            ) AS "participant_count!: i64"
+            FROM competition
+            LEFT JOIN competition_results ON competition.channel_id = competition_results.competition_id
+            ORDER BY competition.created_at DESC, competition.channel_id DESC"#,
+            ApprovalStatus::Approved as i64,
+        ).map(CompetitionHistory::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(history)
+    }
+
+    /// Stores the encrypted credentials for a competition, replacing any existing credentials
+    pub async fn set_competition_credentials(&mut self, competition_id: ChannelId, credentials: &[u8]) -> Result<(), anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        sqlx::query!(
+            "INSERT INTO competition_credentials (competition_id, credentials) VALUES (?, ?)
+            ON CONFLICT(competition_id) DO UPDATE SET credentials = excluded.credentials",
+            competition_id,
+            credentials,
+        )
+        .execute(self.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the competitions which have no stored credentials
+    pub async fn get_competitions_without_credentials(&mut self) -> Result<Vec<Competition>, anyhow::Error> {
+        let competitions = sqlx::query_as!(
+            CompetitionRaw,
+            "SELECT * FROM competition WHERE channel_id NOT IN (SELECT competition_id FROM competition_credentials)",
+        )
+        .map(Competition::from)
+        .fetch_all(self.connection())
+        .await?;
+
+        Ok(competitions)
+    }
+
+    /// Gets the encrypted credentials for a competition
+    pub async fn get_competition_credentials(&mut self, competition_id: ChannelId) -> Result<Vec<u8>, anyhow::Error> {
+        let competition_id = competition_id.get() as i64;
+        let credentials = sqlx::query!(
+            "SELECT credentials FROM competition_credentials WHERE competition_id = ?",
+            competition_id,
+        )
+        .fetch_one(self.connection())
+        .await?
+        .credentials;
+
+        Ok(credentials)
+    }
+
     async fn ensure_user_is_created(&mut self, user_id: UserId) {
         let user_id = user_id.get() as i64;
         // ignore error if user already exists
         let _ = sqlx::query!(
-            "INSERT INTO users (id, email, points) VALUES (?, NULL, 0)",
+            "INSERT INTO users (id, email, points, created_at) VALUES (?, NULL, 0, unixepoch())",
             user_id,
         ).execute(self.connection()).await;
     }
@@ -122,7 +356,7 @@ impl DbConn<'_> {
 
         let user_id = user_id.get() as i64;
         sqlx::query!(
-            "UPDATE users SET email = ? WHERE id = ?",
+            "UPDATE users SET email = ?, verified_at = unixepoch() WHERE id = ?",
             email,
             user_id,
         ).execute(self.connection()).await?;
@@ -130,18 +364,39 @@ impl DbConn<'_> {
         Ok(())
     }
 
-    /// Gives the given user points, creating them if they don't exist
+    /// Gives the given user points, creating them if they don't exist, and records why in the points ledger
     /// 
     /// # Returns
     /// 
     /// Returns the user's points
-    pub async fn give_user_points(&mut self, user_id: UserId, points: i64) -> Result<PointsUpdate, anyhow::Error> {
+    pub async fn give_user_points(
+        &mut self,
+        user_id: UserId,
+        points: i64,
+        reason: &PointsReason,
+        actor_id: Option<UserId>,
+    ) -> Result<PointsUpdate, anyhow::Error> {
         self.ensure_user_is_created(user_id).await;
 
         let user_id = user_id.get() as i64;
+        let actor_id = actor_id.map(|id| id.get() as i64);
+        let (reason, reference_id, note) = reason.to_columns();
+
+        sqlx::query!(
+            "INSERT INTO point_transactions (user_id, delta, reason, reference_id, actor_id, note, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, unixepoch())",
+            user_id,
+            points,
+            reason,
+            reference_id,
+            actor_id,
+            note,
+        ).execute(self.connection()).await?;
+
         let result = sqlx::query_as!(
             PointsUpdateRaw,
-            "UPDATE users SET points = points + ? WHERE id = ? RETURNING id, points, rank",
+            r#"UPDATE users SET points = points + ? WHERE id = ?
+            RETURNING id, points - rank_reset_points AS "points!: i64", rank"#,
             points,
             user_id,
         ).fetch_one(self.connection()).await?;
@@ -149,6 +404,84 @@ impl DbConn<'_> {
         Ok(PointsUpdate::from_raw(result, points))
     }
 
+    /// Sets the user's points to exactly `points`, recording the difference as a manual grant or revocation
+    ///
+    /// The difference is computed by the statement which records it, so a concurrent change can't make it wrong.
+    /// Returns `None` if the user already has `points` points.
+    pub async fn set_user_points(
+        &mut self,
+        user_id: UserId,
+        points: i64,
+        note: &str,
+        actor_id: UserId,
+    ) -> Result<Option<PointsUpdate>, anyhow::Error> {
+        self.ensure_user_is_created(user_id).await;
+
+        let user_id = user_id.get() as i64;
+        let actor_id = actor_id.get() as i64;
+        let (grant_reason, _, _) = PointsReason::ManualGrant { note: note.to_string() }.to_columns();
+        let (revocation_reason, _, _) = PointsReason::Revocation { note: note.to_string() }.to_columns();
+
+        let delta = sqlx::query_scalar!(
+            r#"INSERT INTO point_transactions (user_id, delta, reason, actor_id, note, created_at)
+            SELECT id, ?1 - points, CASE WHEN ?1 > points THEN ?2 ELSE ?3 END, ?4, ?5, unixepoch()
+            FROM users WHERE id = ?6 AND points != ?1
+            RETURNING delta AS "delta!""#,
+            points,
+            grant_reason,
+            revocation_reason,
+            actor_id,
+            note,
+            user_id,
+        ).fetch_optional(self.connection()).await?;
+
+        let Some(delta) = delta else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query_as!(
+            PointsUpdateRaw,
+            r#"UPDATE users SET points = points + ? WHERE id = ?
+            RETURNING id, points - rank_reset_points AS "points!: i64", rank"#,
+            delta,
+            user_id,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(Some(PointsUpdate::from_raw(result, delta)))
+    }
+
+    /// Gets the `count` most recent changes to the user's points
+    pub async fn get_point_transactions_for_user(&mut self, user_id: UserId, count: u32) -> Result<Vec<PointTransaction>, anyhow::Error> {
+        let user_id = user_id.get() as i64;
+        let transactions = sqlx::query_as!(
+            PointTransactionRaw,
+            r#"SELECT id AS "id!", delta, reason, reference_id, actor_id, note, created_at
+            FROM point_transactions WHERE user_id = ? ORDER BY id DESC LIMIT ?"#,
+            user_id,
+            count,
+        ).map(PointTransaction::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(transactions)
+    }
+
+    /// Recomputes every user's cached points from the points ledger
+    /// 
+    /// # Returns
+    /// 
+    /// Returns the number of users whose points changed
+    pub async fn rebuild_points_from_ledger(&mut self) -> Result<u64, anyhow::Error> {
+        let result = sqlx::query!(
+            "UPDATE users SET points = COALESCE(
+                (SELECT SUM(delta) FROM point_transactions WHERE point_transactions.user_id = users.id), 0
+            ) WHERE points != COALESCE(
+                (SELECT SUM(delta) FROM point_transactions WHERE point_transactions.user_id = users.id), 0
+            )",
+        ).execute(self.connection()).await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn set_rank(&mut self, user_id: UserId, rank: Rank) -> Result<(), anyhow::Error> {
         let user_id = user_id.get() as i64;
         let rank: Option<i64> = rank.into();
@@ -161,6 +494,20 @@ impl DbConn<'_> {
         Ok(())
     }
 
+    /// Sets whether the user's rank ups are announced publicly, creating the user if they don't exist
+    pub async fn set_announce_rank_ups(&mut self, user_id: UserId, announce_rank_ups: bool) -> Result<(), anyhow::Error> {
+        self.ensure_user_is_created(user_id).await;
+
+        let user_id = user_id.get() as i64;
+        sqlx::query!(
+            "UPDATE users SET announce_rank_ups = ? WHERE id = ?",
+            announce_rank_ups,
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
     pub async fn get_user_by_id(&mut self, id: UserId) -> Result<User, anyhow::Error> {
         let id = id.get() as i64;
         let user_raw = sqlx::query_as!(
@@ -182,32 +529,543 @@ impl DbConn<'_> {
         Ok(user_raw.into())
     }
 
-    /// Gets the top `count` users with the highest points
-    pub async fn get_users_by_points(&mut self, count: u32) -> Result<Vec<User>, anyhow::Error> {
+    pub async fn get_all_users(&mut self) -> Result<Vec<User>, anyhow::Error> {
         Ok(sqlx::query_as!(
             UserRaw,
-            "SELECT * FROM users ORDER BY points DESC LIMIT ?",
+            "SELECT * FROM users",
+        ).map(User::from)
+            .fetch_all(self.connection()).await?)
+    }
+
+    /// Gets the rank points of every user who earned or lost points since the unix timestamp `since`, lowest first
+    pub async fn get_active_user_points(&mut self, since: i64) -> Result<Vec<i64>, anyhow::Error> {
+        Ok(sqlx::query!(
+            r#"SELECT points - rank_reset_points AS "points!: i64" FROM users WHERE id IN
+            (SELECT user_id FROM point_transactions WHERE created_at >= ?)
+            ORDER BY points - rank_reset_points"#,
+            since,
+        ).map(|user| user.points)
+            .fetch_all(self.connection()).await?)
+    }
+
+    /// Gets the highest rank points of any user, 0 if there are no users
+    pub async fn get_max_rank_points(&mut self) -> Result<i64, anyhow::Error> {
+        Ok(sqlx::query!(
+            r#"SELECT COALESCE(MAX(points - rank_reset_points), 0) AS "points!: i64" FROM users"#,
+        ).fetch_one(self.connection()).await?.points)
+    }
+
+    /// Resets everyone's rank points, only points earned from now on count towards their rank
+    /// 
+    /// Saved ranks are not changed, use `sync_ranks` to update them and the rank roles.
+    pub async fn reset_ranks(&mut self) -> Result<(), anyhow::Error> {
+        sqlx::query!(
+            "UPDATE users SET rank_reset_points = points",
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Creates a season counting points earned from the unix timestamp `start_at` until `end_at`, or until it is closed
+    pub async fn create_season(&mut self, name: &str, start_at: i64, end_at: Option<i64>) -> Result<Season, anyhow::Error> {
+        let season = sqlx::query_as!(
+            SeasonRaw,
+            r#"INSERT INTO seasons (name, start_at, end_at) VALUES (?, ?, ?)
+            RETURNING id AS "id!", name, start_at, end_at, closed_at"#,
+            name,
+            start_at,
+            end_at,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(season.into())
+    }
+
+    /// Gets the season which is currently running, or `None` if there is no season running
+    pub async fn get_current_season(&mut self) -> Result<Option<Season>, anyhow::Error> {
+        let season = sqlx::query_as!(
+            SeasonRaw,
+            "SELECT * FROM seasons WHERE closed_at IS NULL",
+        ).fetch_optional(self.connection()).await?;
+
+        Ok(season.map(Season::from))
+    }
+
+    pub async fn get_season_by_name(&mut self, name: &str) -> Result<Season, anyhow::Error> {
+        let season = sqlx::query_as!(
+            SeasonRaw,
+            r#"SELECT id AS "id!", name, start_at, end_at, closed_at FROM seasons WHERE name = ?"#,
+            name,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(season.into())
+    }
+
+    /// Gets every season, newest first
+    pub async fn get_seasons(&mut self) -> Result<Vec<Season>, anyhow::Error> {
+        Ok(sqlx::query_as!(
+            SeasonRaw,
+            "SELECT * FROM seasons ORDER BY start_at DESC",
+        ).map(Season::from)
+            .fetch_all(self.connection()).await?)
+    }
+
+    /// Adds up the points each user earned during the season from the points ledger, highest first
+    /// 
+    /// Users who did not earn any points are left out.
+    pub async fn get_season_points(&mut self, season: &Season, count: u32) -> Result<Vec<SeasonStanding>, anyhow::Error> {
+        let start_at = season.start_at.unix_timestamp();
+        let end_at = season.points_end().map(|end_at| end_at.unix_timestamp()).unwrap_or(i64::MAX);
+
+        let points = sqlx::query!(
+            r#"SELECT user_id, SUM(delta) AS "points!: i64" FROM point_transactions
+            WHERE created_at >= ? AND created_at < ?
+            GROUP BY user_id HAVING SUM(delta) > 0
+            ORDER BY SUM(delta) DESC LIMIT ?"#,
+            start_at,
+            end_at,
             count,
-        ).map(|user| User::from(user))
+        ).fetch_all(self.connection()).await?;
+
+        Ok(points.into_iter()
+            .enumerate()
+            .map(|(i, user)| SeasonStanding {
+                user_id: UserId::new(user.user_id as u64),
+                points: user.points,
+                placement: i as i64 + 1,
+            })
+            .collect())
+    }
+
+    /// Closes the season now and saves its final standings
+    pub async fn close_season(&mut self, season_id: i64) -> Result<Season, anyhow::Error> {
+        let season = sqlx::query_as!(
+            SeasonRaw,
+            r#"UPDATE seasons SET closed_at = unixepoch() WHERE id = ?
+            RETURNING id AS "id!", name, start_at, end_at, closed_at"#,
+            season_id,
+        ).fetch_one(self.connection()).await?;
+        let season = Season::from(season);
+
+        for standing in self.get_season_points(&season, u32::MAX).await? {
+            let user_id = standing.user_id.get() as i64;
+            sqlx::query!(
+                "INSERT INTO season_standings (season_id, user_id, points, placement) VALUES (?, ?, ?, ?)",
+                season.id,
+                user_id,
+                standing.points,
+                standing.placement,
+            ).execute(self.connection()).await?;
+        }
+
+        Ok(season)
+    }
+
+    /// Gets the top `count` saved standings of a closed season
+    pub async fn get_season_standings(&mut self, season_id: i64, count: u32) -> Result<Vec<SeasonStanding>, anyhow::Error> {
+        Ok(sqlx::query_as!(
+            SeasonStandingRaw,
+            "SELECT user_id, points, placement FROM season_standings
+            WHERE season_id = ? ORDER BY placement LIMIT ?",
+            season_id,
+            count,
+        ).map(SeasonStanding::from)
+            .fetch_all(self.connection()).await?)
+    }
+
+    /// Starts a message point multiplier which lasts for `minutes` minutes
+    pub async fn create_point_multiplier(
+        &mut self,
+        name: &str,
+        multiplier: f64,
+        created_by: UserId,
+        minutes: i64,
+    ) -> Result<PointMultiplier, anyhow::Error> {
+        let created_by = created_by.get() as i64;
+        let multiplier = sqlx::query_as!(
+            PointMultiplierRaw,
+            r#"INSERT INTO point_multipliers (name, multiplier, created_by, expires_at)
+            VALUES (?, ?, ?, unixepoch() + ? * 60)
+            RETURNING id AS "id!", name, multiplier, created_by, expires_at"#,
+            name,
+            multiplier,
+            created_by,
+            minutes,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(multiplier.into())
+    }
+
+    /// Gets every message point multiplier which hasn't expired yet
+    pub async fn get_active_point_multipliers(&mut self) -> Result<Vec<PointMultiplier>, anyhow::Error> {
+        Ok(sqlx::query_as!(
+            PointMultiplierRaw,
+            "SELECT * FROM point_multipliers WHERE expires_at > unixepoch() ORDER BY expires_at",
+        ).map(PointMultiplier::from)
+            .fetch_all(self.connection()).await?)
+    }
+
+    /// Expires a message point multiplier now
+    /// 
+    /// # Returns
+    /// 
+    /// Returns false if there is no running multiplier with the id
+    pub async fn end_point_multiplier(&mut self, multiplier_id: i64) -> Result<bool, anyhow::Error> {
+        let result = sqlx::query!(
+            "UPDATE point_multipliers SET expires_at = unixepoch() WHERE id = ? AND expires_at > unixepoch()",
+            multiplier_id,
+        ).execute(self.connection()).await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Records that a user reacted to a message
+    /// 
+    /// # Returns
+    /// 
+    /// Returns false if the user already reacted to the message before
+    pub async fn record_reaction(&mut self, message_id: MessageId, user_id: UserId) -> Result<bool, anyhow::Error> {
+        let message_id = message_id.get() as i64;
+        let user_id = user_id.get() as i64;
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO reaction_points (message_id, user_id) VALUES (?, ?)",
+            message_id,
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Starts a voice session for the user, unless they are already in one
+    pub async fn start_voice_session(&mut self, user_id: UserId) -> Result<(), anyhow::Error> {
+        let user_id = user_id.get() as i64;
+        sqlx::query!(
+            "INSERT INTO voice_sessions (user_id, joined_at, counted_until)
+            SELECT ?1, unixepoch(), unixepoch()
+            WHERE NOT EXISTS (SELECT 1 FROM voice_sessions WHERE user_id = ?1 AND left_at IS NULL)",
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Gets the voice session the user is in, `None` if they are not in voice
+    pub async fn get_open_voice_session(&mut self, user_id: UserId) -> Result<Option<VoiceSession>, anyhow::Error> {
+        let user_id = user_id.get() as i64;
+        let session = sqlx::query_as!(
+            VoiceSessionRaw,
+            r#"SELECT id AS "id!", user_id, counted_until FROM voice_sessions WHERE user_id = ? AND left_at IS NULL"#,
+            user_id,
+        ).fetch_optional(self.connection()).await?;
+
+        Ok(session.map(VoiceSession::from))
+    }
+
+    /// Gets every voice session which hasn't ended
+    pub async fn get_open_voice_sessions(&mut self) -> Result<Vec<VoiceSession>, anyhow::Error> {
+        Ok(sqlx::query_as!(
+            VoiceSessionRaw,
+            r#"SELECT id AS "id!", user_id, counted_until FROM voice_sessions WHERE left_at IS NULL"#,
+        ).map(VoiceSession::from)
             .fetch_all(self.connection()).await?)
     }
 
+    /// Marks time in the voice session before the unix timestamp `counted_until` as checked for points
+    pub async fn set_voice_session_counted_until(&mut self, session_id: i64, counted_until: i64) -> Result<(), anyhow::Error> {
+        sqlx::query!(
+            "UPDATE voice_sessions SET counted_until = ? WHERE id = ?",
+            counted_until,
+            session_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    pub async fn end_voice_session(&mut self, session_id: i64) -> Result<(), anyhow::Error> {
+        sqlx::query!(
+            "UPDATE voice_sessions SET left_at = unixepoch() WHERE id = ?",
+            session_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Gets the points the user earned from voice since the unix timestamp `since`
+    pub async fn get_voice_points_since(&mut self, user_id: UserId, since: i64) -> Result<i64, anyhow::Error> {
+        let user_id = user_id.get() as i64;
+        let (reason, _, _) = PointsReason::Voice { session_id: None }.to_columns();
+        let points = sqlx::query!(
+            r#"SELECT COALESCE(SUM(delta), 0) AS "points!: i64" FROM point_transactions
+            WHERE user_id = ? AND reason = ? AND created_at >= ?"#,
+            user_id,
+            reason,
+            since,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(points.points)
+    }
+
+    /// Opens check in for a meeting for `minutes` minutes
+    pub async fn create_meeting(&mut self, name: &str, code: &str, opened_by: UserId, minutes: i64) -> Result<Meeting, anyhow::Error> {
+        let opened_by = opened_by.get() as i64;
+        let meeting = sqlx::query_as!(
+            MeetingRaw,
+            r#"INSERT INTO meetings (name, code, opened_by, opened_at, closes_at)
+            VALUES (?, ?, ?, unixepoch(), unixepoch() + ? * 60)
+            RETURNING id AS "id!", name, code, closes_at"#,
+            name,
+            code,
+            opened_by,
+            minutes,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(meeting.into())
+    }
+
+    /// Gets the meeting with the check in code, or `None` if no meeting has the code or check in is closed
+    pub async fn get_open_meeting_by_code(&mut self, code: &str) -> Result<Option<Meeting>, anyhow::Error> {
+        let meeting = sqlx::query_as!(
+            MeetingRaw,
+            "SELECT id, name, code, closes_at FROM meetings WHERE code = ? AND closes_at > unixepoch()",
+            code,
+        ).fetch_optional(self.connection()).await?;
+
+        Ok(meeting.map(Meeting::from))
+    }
+
+    /// Records that a user attended a meeting, creating the user if they don't exist
+    /// 
+    /// # Returns
+    /// 
+    /// Returns false if the user already checked in to the meeting
+    pub async fn add_meeting_attendee(&mut self, meeting_id: i64, user_id: UserId) -> Result<bool, anyhow::Error> {
+        self.ensure_user_is_created(user_id).await;
+
+        let user_id = user_id.get() as i64;
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO meeting_attendance (meeting_id, user_id) VALUES (?, ?)",
+            meeting_id,
+            user_id,
+        ).execute(self.connection()).await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Gets everyone with points, highest first
+    /// 
+    /// If `since` is given only points earned after that unix timestamp are counted.
+    pub async fn get_points_leaderboard(&mut self, since: Option<i64>) -> Result<Vec<LeaderboardEntry>, anyhow::Error> {
+        let entries = match since {
+            Some(since) => sqlx::query_as!(
+                LeaderboardEntryRaw,
+                r#"SELECT user_id AS id, SUM(delta) AS "score!: i64" FROM point_transactions
+                WHERE created_at >= ?
+                GROUP BY user_id HAVING SUM(delta) > 0
+                ORDER BY SUM(delta) DESC"#,
+                since,
+            ).map(LeaderboardEntry::from)
+                .fetch_all(self.connection()).await?,
+            None => sqlx::query_as!(
+                LeaderboardEntryRaw,
+                r#"SELECT id, points AS "score!: i64" FROM users WHERE points > 0 ORDER BY points DESC"#,
+            ).map(LeaderboardEntry::from)
+                .fetch_all(self.connection()).await?,
+        };
+
+        Ok(entries)
+    }
+
+    /// Gets everyone who solved a challenge, with the most challenges solved first
+    /// 
+    /// Only challenges matching the category and competition are counted if they are given,
+    /// and only solves after the unix timestamp `since` if it is given.
+    pub async fn get_solves_leaderboard(
+        &mut self,
+        category: Option<ChallengeType>,
+        competition_id: Option<ChannelId>,
+        since: Option<i64>,
+    ) -> Result<Vec<LeaderboardEntry>, anyhow::Error> {
+        let category = category.map(|category| category as i64);
+        let competition_id = competition_id.map(|id| id.get() as i64);
+
+        let entries = sqlx::query_as!(
+            LeaderboardEntryRaw,
+            r#"SELECT user_solves.user_id AS id, COUNT(DISTINCT solves.challenge_id) AS "score!: i64" FROM solves
+            INNER JOIN user_solves ON solves.id = user_solves.solve_id
+            INNER JOIN challenges ON solves.challenge_id = challenges.id
+            WHERE solves.approval_status = ?1
+            AND (?2 IS NULL OR challenges.category = ?2)
+            AND (?3 IS NULL OR challenges.competition_id = ?3)
+            AND (?4 IS NULL OR solves.created_at >= ?4)
+            GROUP BY user_solves.user_id
+            ORDER BY COUNT(DISTINCT solves.challenge_id) DESC"#,
+            ApprovalStatus::Approved as i64,
+            category,
+            competition_id,
+            since,
+        ).map(LeaderboardEntry::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(entries)
+    }
+
+    /// Gets aggregate numbers about the whole server
+    /// 
+    /// Users are counted as active if their points changed after the unix timestamp `active_since`,
+    /// and at most `challenge_count` of the challenges with the most activity are returned.
+    pub async fn get_server_stats(&mut self, active_since: i64, challenge_count: u32) -> Result<ServerStats, anyhow::Error> {
+        let verified_count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM users WHERE email IS NOT NULL"#,
+        ).fetch_one(self.connection()).await?;
+
+        let active_count = sqlx::query_scalar!(
+            r#"SELECT COUNT(DISTINCT user_id) AS "count!: i64" FROM point_transactions WHERE created_at >= ?"#,
+            active_since,
+        ).fetch_one(self.connection()).await?;
+
+        let competition_count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM competition"#,
+        ).fetch_one(self.connection()).await?;
+
+        let solves_by_category = sqlx::query_as!(
+            CategorySolvesRaw,
+            r#"SELECT challenges.category, COUNT(*) AS "solve_count!: i64" FROM solves
+            INNER JOIN challenges ON solves.challenge_id = challenges.id
+            WHERE solves.approval_status = ?
+            GROUP BY challenges.category
+            ORDER BY COUNT(*) DESC"#,
+            ApprovalStatus::Approved as i64,
+        ).fetch_all(self.connection()).await?
+            .into_iter()
+            .map(|row| (
+                ChallengeType::from_repr(row.category).expect("invalid category returned from database"),
+                row.solve_count,
+            ))
+            .collect();
+
+        let approval_latencies = sqlx::query_scalar!(
+            r#"SELECT approved_at - created_at AS "latency!: i64" FROM solves
+            WHERE approval_status = ? AND approved_at IS NOT NULL
+            ORDER BY approved_at - created_at"#,
+            ApprovalStatus::Approved as i64,
+        ).fetch_all(self.connection()).await?;
+
+        let challenges_by_activity = sqlx::query_as!(
+            ChallengeActivityRaw,
+            r#"SELECT name AS "name!", channel_id, activity AS "activity!: i64" FROM (
+                SELECT challenges.name, challenges.channel_id,
+                    (SELECT COUNT(*) FROM challenge_notes WHERE challenge_notes.challenge_id = challenges.id)
+                    + (SELECT COUNT(*) FROM solves WHERE solves.challenge_id = challenges.id)
+                    + (SELECT COUNT(*) FROM writeups WHERE writeups.challenge_id = challenges.id) AS activity
+                FROM challenges
+            )
+            WHERE activity > 0
+            ORDER BY activity DESC
+            LIMIT ?"#,
+            challenge_count,
+        ).map(ChallengeActivity::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(ServerStats {
+            verified_count,
+            active_count,
+            competition_count,
+            solves_by_category,
+            approval_latencies,
+            challenges_by_activity,
+        })
+    }
+
+    /// Records that the user earned the achievement
+    /// 
+    /// Returns `None` if the user already had the achievement.
+    pub async fn award_achievement(&mut self, user_id: UserId, achievement: Achievement) -> Result<Option<UserAchievement>, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let (achievement, reference_id) = achievement.to_columns();
+
+        let user_achievement = sqlx::query_as!(
+            UserAchievementRaw,
+            r#"INSERT OR IGNORE INTO user_achievements (user_id, achievement, reference_id, earned_at)
+            VALUES (?, ?, ?, unixepoch())
+            RETURNING id AS "id!", user_id AS "user_id!", achievement AS "achievement!", reference_id AS "reference_id!", earned_at AS "earned_at!""#,
+            id,
+            achievement,
+            reference_id,
+        ).map(UserAchievement::from)
+            .fetch_optional(self.connection()).await?;
+
+        Ok(user_achievement)
+    }
+
+    /// Gets every achievement the user has earned, most recent first
+    pub async fn get_achievements_for_user(&mut self, user_id: UserId) -> Result<Vec<UserAchievement>, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let achievements = sqlx::query_as!(
+            UserAchievementRaw,
+            r#"SELECT id AS "id!", user_id AS "user_id!", achievement AS "achievement!", reference_id AS "reference_id!", earned_at AS "earned_at!"
+            FROM user_achievements WHERE user_id = ? ORDER BY earned_at DESC, id DESC"#,
+            id,
+        ).map(UserAchievement::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(achievements)
+    }
+
+    /// Gets the message point limits of every user who earned message points on `day`
+    pub async fn get_message_point_limits(&mut self, day: i64) -> Result<Vec<MessagePointLimit>, anyhow::Error> {
+        let limits = sqlx::query_as!(
+            MessagePointLimitRaw,
+            "SELECT * FROM message_point_limits WHERE day = ?",
+            day,
+        ).map(MessagePointLimit::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(limits)
+    }
+
+    pub async fn set_message_point_limit(&mut self, limit: MessagePointLimit) -> Result<(), anyhow::Error> {
+        self.ensure_user_is_created(limit.user_id).await;
+
+        let limit_raw: MessagePointLimitRaw = limit.into();
+        sqlx::query!(
+            "INSERT INTO message_point_limits (user_id, day, points_today, last_awarded_at) VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+            day = excluded.day, points_today = excluded.points_today, last_awarded_at = excluded.last_awarded_at",
+            limit_raw.user_id,
+            limit_raw.day,
+            limit_raw.points_today,
+            limit_raw.last_awarded_at,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
     /// Creates a new challenge, returning its id
     pub async fn create_challenge(&mut self, challenge: Challenge) -> Result<i64, anyhow::Error> {
         let challenge_raw: ChallengeRaw = challenge.into();
 
         let id = sqlx::query!(
-            "INSERT INTO challenges (competition_id, name, category, channel_id)
-            VALUES (?, ?, ?, ?) RETURNING id",
+            "INSERT INTO challenges (competition_id, name, category, channel_id, created_at)
+            VALUES (?, ?, ?, ?, ?) RETURNING id",
             challenge_raw.competition_id,
             challenge_raw.name,
             challenge_raw.category,
             challenge_raw.channel_id,
+            challenge_raw.created_at,
         ).fetch_one(self.connection()).await?.id;
 
         Ok(id)
     }
 
+    pub async fn get_challenge_by_id(&mut self, challenge_id: i64) -> Result<Challenge, anyhow::Error> {
+        let challenge = sqlx::query_as!(
+            ChallengeRaw,
+            "SELECT * FROM challenges WHERE id = ?",
+            challenge_id,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(challenge.into())
+    }
+
     pub async fn get_challenge_by_channel_id(&mut self, challenge_id: ChannelId) -> Result<Challenge, anyhow::Error> {
         let challenge_id = challenge_id.get() as i64;
 
@@ -220,18 +1078,76 @@ impl DbConn<'_> {
         Ok(challenge.into())
     }
 
+    /// Gets every challenge in the competition
+    pub async fn get_challenges_for_competition(&mut self, competition_id: ChannelId) -> Result<Vec<Challenge>, anyhow::Error> {
+        let id = competition_id.get() as i64;
+        let challenges = sqlx::query_as!(
+            ChallengeRaw,
+            "SELECT * FROM challenges WHERE competition_id = ? ORDER BY category, name",
+            id,
+        ).map(Challenge::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(challenges)
+    }
+
+    pub async fn set_challenge_notes_message(&mut self, challenge_id: i64, message_id: MessageId) -> Result<(), anyhow::Error> {
+        let message_id = message_id.get() as i64;
+        sqlx::query!(
+            "UPDATE challenges SET notes_message_id = ? WHERE id = ?",
+            message_id,
+            challenge_id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Creates a new note and returns the note id
+    pub async fn create_note(&mut self, note: Note) -> Result<i64, anyhow::Error> {
+        self.ensure_user_is_created(note.user_id).await;
+
+        let note_raw: NoteRaw = note.into();
+
+        let OutputId { id } = sqlx::query_as!(
+            OutputId,
+            "INSERT INTO challenge_notes (challenge_id, user_id, content, file_name, file_data)
+            VALUES (?, ?, ?, ?, ?) RETURNING id",
+            note_raw.challenge_id,
+            note_raw.user_id,
+            note_raw.content,
+            note_raw.file_name,
+            note_raw.file_data,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(id)
+    }
+
+    /// Gets all notes for the challenge, oldest first
+    pub async fn get_notes_for_challenge(&mut self, challenge_id: i64) -> Result<Vec<Note>, anyhow::Error> {
+        let notes = sqlx::query_as!(
+            NoteRaw,
+            "SELECT * FROM challenge_notes WHERE challenge_id = ? ORDER BY id",
+            challenge_id,
+        ).map(Note::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(notes)
+    }
+
     /// Creates a new solve solved by the given users and returns the solve id
     pub async fn create_solve(&mut self, solve: Solve, users: &[UserId]) -> Result<i64, anyhow::Error> {
         let solve_raw: SolveRaw = solve.into();
 
         let OutputId { id: solve_id } = sqlx::query_as!(
             OutputId,
-            "INSERT INTO solves (challenge_id, approval_message_id, flag, approval_status)
-            VALUES (?, ?, ?, ?) RETURNING id",
+            "INSERT INTO solves (challenge_id, approval_message_id, flag, approval_status, created_at, approved_at)
+            VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
             solve_raw.challenge_id,
             solve_raw.approval_message_id,
             solve_raw.flag,
             solve_raw.approval_status,
+            solve_raw.created_at,
+            solve_raw.approved_at,
         ).fetch_one(self.connection()).await?;
 
         for user_id in users {
@@ -240,7 +1156,7 @@ impl DbConn<'_> {
             // ensure user exists first
             // ignore error if user already exists
             let _ = sqlx::query!(
-                "INSERT INTO users (id, email, points) VALUES (?, NULL, 0)",
+                "INSERT INTO users (id, email, points, created_at) VALUES (?, NULL, 0, unixepoch())",
                 user_id,
             ).execute(self.connection()).await;
 
@@ -265,6 +1181,7 @@ impl DbConn<'_> {
         Ok(solve_raw.into())
     }
 
+    /// Gets the challenges the user has solved, most recent solve first
     pub async fn get_solved_challenges_for_user(&mut self, user_id: UserId) -> Result<Vec<Challenge>, anyhow::Error> {
         let id = user_id.get() as i64;
         let solves = sqlx::query_as!(
@@ -272,7 +1189,8 @@ impl DbConn<'_> {
             "SELECT challenges.* FROM solves
             INNER JOIN user_solves ON solves.id = user_solves.solve_id
             INNER JOIN challenges ON solves.challenge_id = challenges.id
-            WHERE user_solves.user_id = ? AND solves.approval_status = ?",
+            WHERE user_solves.user_id = ? AND solves.approval_status = ?
+            ORDER BY solves.id DESC",
             id,
             ApprovalStatus::Approved as i64,
         ).map(|solve| Challenge::from(solve))
@@ -281,22 +1199,167 @@ impl DbConn<'_> {
         Ok(solves)
     }
 
+    /// Gets every challenge in the competition with at least one approved solve
+    pub async fn get_solved_challenges_for_competition(&mut self, competition_id: ChannelId) -> Result<Vec<Challenge>, anyhow::Error> {
+        let id = competition_id.get() as i64;
+        let challenges = sqlx::query_as!(
+            ChallengeRaw,
+            "SELECT DISTINCT challenges.* FROM challenges
+            INNER JOIN solves ON solves.challenge_id = challenges.id
+            WHERE challenges.competition_id = ? AND solves.approval_status = ?",
+            id,
+            ApprovalStatus::Approved as i64,
+        ).map(Challenge::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(challenges)
+    }
+
+    /// Gets the id of the first solve approved in the competition, `None` if nothing is solved yet
+    /// 
+    /// Solves approved before approval times were recorded are ordered by when they were submitted.
+    pub async fn get_first_blood_solve_id(&mut self, competition_id: ChannelId) -> Result<Option<i64>, anyhow::Error> {
+        let id = competition_id.get() as i64;
+        let solve_id = sqlx::query_scalar!(
+            "SELECT solves.id FROM solves
+            INNER JOIN challenges ON solves.challenge_id = challenges.id
+            WHERE challenges.competition_id = ? AND solves.approval_status = ?
+            ORDER BY COALESCE(solves.approved_at, solves.created_at), solves.id
+            LIMIT 1",
+            id,
+            ApprovalStatus::Approved as i64,
+        ).fetch_optional(self.connection()).await?;
+
+        Ok(solve_id)
+    }
+
+    /// Gets the `count` users with the most approved solves in the competition
+    pub async fn get_top_solvers_for_competition(&mut self, competition_id: ChannelId, count: u32) -> Result<Vec<SolveCount>, anyhow::Error> {
+        let id = competition_id.get() as i64;
+        let solvers = sqlx::query_as!(
+            SolveCountRaw,
+            "SELECT user_solves.user_id AS id, COUNT(DISTINCT solves.challenge_id) AS solve_count FROM solves
+            INNER JOIN user_solves ON solves.id = user_solves.solve_id
+            INNER JOIN challenges ON solves.challenge_id = challenges.id
+            WHERE challenges.competition_id = ? AND solves.approval_status = ?
+            GROUP BY user_solves.user_id
+            ORDER BY solve_count DESC
+            LIMIT ?",
+            id,
+            ApprovalStatus::Approved as i64,
+            count,
+        ).map(SolveCount::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(solvers)
+    }
+
     /// Updates the flag, and approval status of the given solve
     pub async fn update_solve(&mut self, solve: Solve) -> Result<(), anyhow::Error> {
         let solve_raw: SolveRaw = solve.into();
 
         sqlx::query!(
-            "UPDATE solves SET flag = ?, approval_status = ? WHERE id = ?",
+            "UPDATE solves SET flag = ?, approval_status = ?, approved_at = ? WHERE id = ?",
             solve_raw.flag,
             solve_raw.approval_status,
+            solve_raw.approved_at,
             solve_raw.id,
         ).execute(self.connection()).await?;
 
         Ok(())
     }
 
-    /// Gives all the participants of this solve some points
-    pub async fn give_points_for_solve(&mut self, solve_id: i64, points: i64) -> Result<Vec<PointsUpdate>, anyhow::Error> {
+    /// Creates a new writeup and returns the writeup id
+    pub async fn create_writeup(&mut self, writeup: Writeup) -> Result<i64, anyhow::Error> {
+        self.ensure_user_is_created(writeup.user_id).await;
+
+        let writeup_raw: WriteupRaw = writeup.into();
+
+        let OutputId { id } = sqlx::query_as!(
+            OutputId,
+            "INSERT INTO writeups (challenge_id, user_id, url, approval_message_id, approval_status)
+            VALUES (?, ?, ?, ?, ?) RETURNING id",
+            writeup_raw.challenge_id,
+            writeup_raw.user_id,
+            writeup_raw.url,
+            writeup_raw.approval_message_id,
+            writeup_raw.approval_status,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(id)
+    }
+
+    pub async fn get_writeup_by_approval_message_id(&mut self, message_id: MessageId) -> Result<Writeup, anyhow::Error> {
+        let id = message_id.get() as i64;
+        let writeup_raw = sqlx::query_as!(
+            WriteupRaw,
+            "SELECT * FROM writeups WHERE approval_message_id = ?",
+            id,
+        ).fetch_one(self.connection()).await?;
+
+        Ok(writeup_raw.into())
+    }
+
+    /// Gets all writeups which have not been declined, newest first
+    /// 
+    /// Each filter is ignored if it is `None`
+    pub async fn get_writeups(
+        &mut self,
+        competition_id: Option<ChannelId>,
+        category: Option<ChallengeType>,
+        user_id: Option<UserId>,
+    ) -> Result<Vec<Writeup>, anyhow::Error> {
+        let competition_id = competition_id.map(|id| id.get() as i64);
+        let category = category.map(|category| category as i64);
+        let user_id = user_id.map(|id| id.get() as i64);
+
+        let writeups = sqlx::query_as!(
+            WriteupRaw,
+            "SELECT writeups.* FROM writeups
+            INNER JOIN challenges ON writeups.challenge_id = challenges.id
+            WHERE writeups.approval_status != ?
+            AND (?2 IS NULL OR challenges.competition_id = ?2)
+            AND (?3 IS NULL OR challenges.category = ?3)
+            AND (?4 IS NULL OR writeups.user_id = ?4)
+            ORDER BY writeups.id DESC",
+            ApprovalStatus::Declined as i64,
+            competition_id,
+            category,
+            user_id,
+        ).map(Writeup::from)
+            .fetch_all(self.connection()).await?;
+
+        Ok(writeups)
+    }
+
+    /// Updates the approval status of the given writeup
+    pub async fn update_writeup(&mut self, writeup: Writeup) -> Result<(), anyhow::Error> {
+        let writeup_raw: WriteupRaw = writeup.into();
+
+        sqlx::query!(
+            "UPDATE writeups SET url = ?, approval_status = ? WHERE id = ?",
+            writeup_raw.url,
+            writeup_raw.approval_status,
+            writeup_raw.id,
+        ).execute(self.connection()).await?;
+
+        Ok(())
+    }
+
+    /// Gives all the participants of this solve some points, and records the solve in the points ledger
+    pub async fn give_points_for_solve(&mut self, solve_id: i64, points: i64, approver_id: UserId) -> Result<Vec<PointsUpdate>, anyhow::Error> {
+        let approver_id = approver_id.get() as i64;
+        let (reason, _, _) = PointsReason::Solve { solve_id }.to_columns();
+
+        sqlx::query!(
+            "INSERT INTO point_transactions (user_id, delta, reason, reference_id, actor_id, note, created_at)
+            SELECT DISTINCT user_id, ?, ?, solve_id, ?, NULL, unixepoch() FROM user_solves WHERE solve_id = ?",
+            points,
+            reason,
+            approver_id,
+            solve_id,
+        ).execute(self.connection()).await?;
+
         let result = sqlx::query_as!(
             PointsUpdateRaw,
             "UPDATE users SET points = points + ? WHERE id IN
@@ -311,16 +1374,60 @@ impl DbConn<'_> {
     }
 }
 
+struct SolveCountRaw {
+    id: i64,
+    solve_count: i64,
+}
+
+/// Number of challenges a user has solved
+#[derive(Debug, Clone)]
+pub struct SolveCount {
+    pub user_id: UserId,
+    pub solve_count: i64,
+}
+
+impl From<SolveCountRaw> for SolveCount {
+    fn from(value: SolveCountRaw) -> Self {
+        SolveCount {
+            user_id: UserId::new(value.id as u64),
+            solve_count: value.solve_count,
+        }
+    }
+}
+
+struct LeaderboardEntryRaw {
+    id: i64,
+    score: i64,
+}
+
+/// A user's score on a leaderboard, like their points or number of solves
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub user_id: UserId,
+    pub score: i64,
+}
+
+impl From<LeaderboardEntryRaw> for LeaderboardEntry {
+    fn from(value: LeaderboardEntryRaw) -> Self {
+        LeaderboardEntry {
+            user_id: UserId::new(value.id as u64),
+            score: value.score,
+        }
+    }
+}
+
 struct PointsUpdateRaw {
     /// Id of user with changed points
     id: i64,
-    /// New points for the user
+    /// New rank points for the user
     points: i64,
     /// Old rank for user
     rank: Option<i64>,
 }
 
 /// Represents a change in points that occured in a sql query
+/// 
+/// Points are the rank points of the user, the points earned since ranks were last reset.
 #[derive(Debug)]
 pub struct PointsUpdate {
     pub user_id: UserId,
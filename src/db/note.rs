@@ -0,0 +1,62 @@
+use serenity::all::UserId;
+
+#[derive(Debug, Clone)]
+pub struct NoteRaw {
+    pub id: i64,
+    pub challenge_id: i64,
+    pub user_id: i64,
+    pub content: String,
+    pub file_name: Option<String>,
+    pub file_data: Option<Vec<u8>>,
+}
+
+impl From<Note> for NoteRaw {
+    fn from(value: Note) -> Self {
+        let (file_name, file_data) = match value.file {
+            Some(file) => (Some(file.name), Some(file.data)),
+            None => (None, None),
+        };
+
+        NoteRaw {
+            id: value.id,
+            challenge_id: value.challenge_id,
+            user_id: value.user_id.get() as i64,
+            content: value.content,
+            file_name,
+            file_data,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NoteFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// A note left in a challenge channel, like offsets or a partial script
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub id: i64,
+    pub challenge_id: i64,
+    pub user_id: UserId,
+    pub content: String,
+    pub file: Option<NoteFile>,
+}
+
+impl From<NoteRaw> for Note {
+    fn from(value: NoteRaw) -> Self {
+        let file = match (value.file_name, value.file_data) {
+            (Some(name), Some(data)) => Some(NoteFile { name, data }),
+            _ => None,
+        };
+
+        Note {
+            id: value.id,
+            challenge_id: value.challenge_id,
+            user_id: UserId::new(value.user_id as u64),
+            content: value.content,
+            file,
+        }
+    }
+}
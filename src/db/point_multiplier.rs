@@ -0,0 +1,40 @@
+use serenity::all::{Timestamp, UserId};
+
+#[derive(Debug, Clone)]
+pub struct PointMultiplierRaw {
+    pub id: i64,
+    pub name: String,
+    pub multiplier: f64,
+    pub created_by: i64,
+    pub expires_at: i64,
+}
+
+/// Temporary multiplier for every message point earned, like a double points weekend
+#[derive(Debug, Clone)]
+pub struct PointMultiplier {
+    pub id: i64,
+    pub name: String,
+    pub multiplier: f64,
+    /// Officer who started the multiplier
+    pub created_by: UserId,
+    pub expires_at: Timestamp,
+}
+
+impl PointMultiplier {
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.expires_at > now
+    }
+}
+
+impl From<PointMultiplierRaw> for PointMultiplier {
+    fn from(value: PointMultiplierRaw) -> Self {
+        PointMultiplier {
+            id: value.id,
+            name: value.name,
+            multiplier: value.multiplier,
+            created_by: UserId::new(value.created_by as u64),
+            expires_at: Timestamp::from_unix_timestamp(value.expires_at)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use serenity::all::{Timestamp, UserId};
+
+#[derive(Debug, Clone)]
+pub struct PointTransactionRaw {
+    pub id: i64,
+    pub delta: i64,
+    pub reason: i64,
+    pub reference_id: Option<i64>,
+    pub actor_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: i64,
+}
+
+/// Why a user's points changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointsReason {
+    /// Points earned before the ledger existed
+    Legacy,
+    Message,
+    Solve { solve_id: i64 },
+    ManualGrant { note: String },
+    Writeup { writeup_id: i64 },
+    Revocation { note: String },
+    /// Time spent in a voice channel during a competition, `None` for points given before sessions were saved
+    Voice { session_id: Option<i64> },
+    /// Someone reacted to the user's message
+    Reaction { message_id: i64 },
+    Attendance { meeting_id: i64 },
+    Achievement { achievement_id: i64 },
+}
+
+impl PointsReason {
+    /// Splits the reason into the `reason`, `reference_id`, and `note` columns
+    pub fn to_columns(&self) -> (i64, Option<i64>, Option<&str>) {
+        match self {
+            Self::Legacy => (0, None, None),
+            Self::Message => (1, None, None),
+            Self::Solve { solve_id } => (2, Some(*solve_id), None),
+            Self::ManualGrant { note } => (3, None, Some(note)),
+            Self::Writeup { writeup_id } => (4, Some(*writeup_id), None),
+            Self::Revocation { note } => (5, None, Some(note)),
+            Self::Voice { session_id } => (6, *session_id, None),
+            Self::Reaction { message_id } => (7, Some(*message_id), None),
+            Self::Attendance { meeting_id } => (8, Some(*meeting_id), None),
+            Self::Achievement { achievement_id } => (9, Some(*achievement_id), None),
+        }
+    }
+
+    fn from_columns(reason: i64, reference_id: Option<i64>, note: Option<String>) -> Self {
+        match reason {
+            0 => Self::Legacy,
+            1 => Self::Message,
+            2 => Self::Solve { solve_id: reference_id.unwrap_or_default() },
+            3 => Self::ManualGrant { note: note.unwrap_or_default() },
+            4 => Self::Writeup { writeup_id: reference_id.unwrap_or_default() },
+            5 => Self::Revocation { note: note.unwrap_or_default() },
+            6 => Self::Voice { session_id: reference_id },
+            7 => Self::Reaction { message_id: reference_id.unwrap_or_default() },
+            8 => Self::Attendance { meeting_id: reference_id.unwrap_or_default() },
+            9 => Self::Achievement { achievement_id: reference_id.unwrap_or_default() },
+            _ => panic!("invalid points reason returned from database"),
+        }
+    }
+}
+
+impl std::fmt::Display for PointsReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Legacy => write!(f, "earned before history was recorded"),
+            Self::Message => write!(f, "messages"),
+            Self::Solve { solve_id } => write!(f, "solve #{solve_id}"),
+            Self::ManualGrant { note } => write!(f, "grant: {note}"),
+            Self::Writeup { writeup_id } => write!(f, "writeup #{writeup_id}"),
+            Self::Revocation { note } => write!(f, "revocation: {note}"),
+            Self::Voice { session_id: Some(session_id) } => write!(f, "voice session #{session_id}"),
+            Self::Voice { session_id: None } => write!(f, "time in voice"),
+            Self::Reaction { .. } => write!(f, "reaction"),
+            Self::Attendance { meeting_id } => write!(f, "meeting #{meeting_id} attendance"),
+            Self::Achievement { achievement_id } => write!(f, "achievement #{achievement_id}"),
+        }
+    }
+}
+
+/// A single change to a user's points
+#[derive(Debug, Clone)]
+pub struct PointTransaction {
+    pub id: i64,
+    pub delta: i64,
+    pub reason: PointsReason,
+    /// User who made the change, `None` if it was automatic
+    pub actor_id: Option<UserId>,
+    pub created_at: Timestamp,
+}
+
+impl From<PointTransactionRaw> for PointTransaction {
+    fn from(value: PointTransactionRaw) -> Self {
+        PointTransaction {
+            id: value.id,
+            delta: value.delta,
+            reason: PointsReason::from_columns(value.reason, value.reference_id, value.note),
+            actor_id: value.actor_id.map(|id| UserId::new(id as u64)),
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use serenity::all::{Timestamp, UserId};
+
+#[derive(Debug, Clone)]
+pub struct SeasonRaw {
+    pub id: i64,
+    pub name: String,
+    pub start_at: i64,
+    pub end_at: Option<i64>,
+    pub closed_at: Option<i64>,
+}
+
+/// A period of time, like a semester, where points are counted separately from lifetime points
+#[derive(Debug, Clone)]
+pub struct Season {
+    pub id: i64,
+    pub name: String,
+    /// Points earned from this time on count towards the season
+    pub start_at: Timestamp,
+    /// Points earned from this time on no longer count towards the season, `None` if it runs until it is closed
+    pub end_at: Option<Timestamp>,
+    /// When the season was closed, `None` while the season is running
+    pub closed_at: Option<Timestamp>,
+}
+
+impl Season {
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+    }
+
+    /// Gets the time points stop counting towards the season, the end date or when it was closed if that was earlier
+    pub fn points_end(&self) -> Option<Timestamp> {
+        match (self.end_at, self.closed_at) {
+            (Some(end_at), Some(closed_at)) => Some(std::cmp::min(end_at, closed_at)),
+            (end_at, closed_at) => end_at.or(closed_at),
+        }
+    }
+}
+
+impl From<SeasonRaw> for Season {
+    fn from(value: SeasonRaw) -> Self {
+        Season {
+            id: value.id,
+            name: value.name,
+            start_at: Timestamp::from_unix_timestamp(value.start_at)
+                .expect("invalid timestamp returned from database"),
+            end_at: value.end_at.map(|end_at| Timestamp::from_unix_timestamp(end_at)
+                .expect("invalid timestamp returned from database")),
+            closed_at: value.closed_at.map(|closed_at| Timestamp::from_unix_timestamp(closed_at)
+                .expect("invalid timestamp returned from database")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SeasonStandingRaw {
+    pub user_id: i64,
+    pub points: i64,
+    pub placement: i64,
+}
+
+/// How many points a user earned during a season
+#[derive(Debug, Clone)]
+pub struct SeasonStanding {
+    pub user_id: UserId,
+    pub points: i64,
+    /// 1 for the user with the most points
+    pub placement: i64,
+}
+
+impl From<SeasonStandingRaw> for SeasonStanding {
+    fn from(value: SeasonStandingRaw) -> Self {
+        SeasonStanding {
+            user_id: UserId::new(value.user_id as u64),
+            points: value.points,
+            placement: value.placement,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use serenity::all::ChannelId;
+
+use super::ChallengeType;
+
+#[derive(Debug, Clone)]
+pub struct CategorySolvesRaw {
+    pub category: i64,
+    pub solve_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChallengeActivityRaw {
+    pub name: String,
+    pub channel_id: Option<i64>,
+    pub activity: i64,
+}
+
+/// How much work went into a challenge, counted from notes, solve submissions, and writeups
+#[derive(Debug, Clone)]
+pub struct ChallengeActivity {
+    pub name: String,
+    pub channel_id: Option<ChannelId>,
+    pub activity: i64,
+}
+
+impl From<ChallengeActivityRaw> for ChallengeActivity {
+    fn from(value: ChallengeActivityRaw) -> Self {
+        ChallengeActivity {
+            name: value.name,
+            channel_id: value.channel_id.map(|id| ChannelId::new(id as u64)),
+            activity: value.activity,
+        }
+    }
+}
+
+/// Aggregate numbers about the whole server, used for club reports
+#[derive(Debug, Clone)]
+pub struct ServerStats {
+    pub verified_count: i64,
+    /// Number of users who earned or lost points in the requested period
+    pub active_count: i64,
+    pub competition_count: i64,
+    /// Approved solves in each category, categories without solves are left out
+    pub solves_by_category: Vec<(ChallengeType, i64)>,
+    /// Seconds between submitting and approving each solve, sorted from fastest to slowest
+    ///
+    /// Solves approved before approval times were recorded are left out.
+    pub approval_latencies: Vec<i64>,
+    /// Challenges with the most notes, solve submissions, and writeups, most activity first
+    pub challenges_by_activity: Vec<ChallengeActivity>,
+}
+
+impl ServerStats {
+    /// Mean seconds taken to approve a solve, `None` if no approval times were recorded
+    pub fn mean_approval_latency(&self) -> Option<i64> {
+        if self.approval_latencies.is_empty() {
+            return None;
+        }
+
+        Some(self.approval_latencies.iter().sum::<i64>() / self.approval_latencies.len() as i64)
+    }
+
+    /// Median seconds taken to approve a solve, `None` if no approval times were recorded
+    pub fn median_approval_latency(&self) -> Option<i64> {
+        self.approval_latencies.get(self.approval_latencies.len() / 2).copied()
+    }
+}
@@ -1,4 +1,4 @@
-use serenity::all::MessageId;
+use serenity::all::{MessageId, Timestamp};
 use strum::FromRepr;
 
 #[derive(Debug, Clone)]
@@ -8,6 +8,8 @@ pub struct SolveRaw {
     pub approval_message_id: i64,
     pub flag: String,
     pub approval_status: i64,
+    pub created_at: i64,
+    pub approved_at: Option<i64>,
 }
 
 impl From<Solve> for SolveRaw {
@@ -18,6 +20,8 @@ impl From<Solve> for SolveRaw {
             approval_message_id: value.approval_message_id.get() as i64,
             flag: value.flag,
             approval_status: value.approval_status as i64,
+            created_at: value.created_at.unix_timestamp(),
+            approved_at: value.approved_at.map(|approved_at| approved_at.unix_timestamp()),
         }
     }
 }
@@ -37,6 +41,10 @@ pub struct Solve {
     pub approval_message_id: MessageId,
     pub flag: String,
     pub approval_status: ApprovalStatus,
+    /// When the solve was submitted
+    pub created_at: Timestamp,
+    /// When the solve was approved, `None` if it is not approved or was approved before this was recorded
+    pub approved_at: Option<Timestamp>,
 }
 
 impl From<SolveRaw> for Solve {
@@ -48,6 +56,10 @@ impl From<SolveRaw> for Solve {
             flag: value.flag,
             approval_status: ApprovalStatus::from_repr(value.approval_status)
                 .expect("invalid approval status returned from database"),
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
+            approved_at: value.approved_at.map(|approved_at| Timestamp::from_unix_timestamp(approved_at)
+                .expect("invalid timestamp returned from database")),
         }
     }
 }
\ No newline at end of file
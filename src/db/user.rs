@@ -1,4 +1,4 @@
-use serenity::all::UserId;
+use serenity::all::{Timestamp, UserId};
 
 use crate::points::Rank;
 
@@ -8,6 +8,10 @@ pub struct UserRaw {
     pub email: Option<String>,
     pub points: i64,
     pub rank: Option<i64>,
+    pub announce_rank_ups: bool,
+    pub created_at: i64,
+    pub verified_at: Option<i64>,
+    pub rank_reset_points: i64,
 }
 
 impl From<User> for UserRaw {
@@ -17,6 +21,10 @@ impl From<User> for UserRaw {
             email: value.email,
             points: value.points,
             rank: value.rank.into(),
+            announce_rank_ups: value.announce_rank_ups,
+            created_at: value.created_at.unix_timestamp(),
+            verified_at: value.verified_at.map(|verified_at| verified_at.unix_timestamp()),
+            rank_reset_points: value.rank_reset_points,
         }
     }
 }
@@ -27,12 +35,25 @@ pub struct User {
     pub email: Option<String>,
     pub points: i64,
     pub rank: Rank,
+    /// If false rank ups are not announced publicly
+    pub announce_rank_ups: bool,
+    /// When the bot first saw the user
+    pub created_at: Timestamp,
+    /// When the user verified their email, `None` if not verified or verified before this was recorded
+    pub verified_at: Option<Timestamp>,
+    /// Points the user had when ranks were last reset
+    pub rank_reset_points: i64,
 }
 
 impl User {
     pub fn is_verified(&self) -> bool {
         self.email.is_some()
     }
+
+    /// Gets the points which count towards the user's rank, the points earned since ranks were last reset
+    pub fn rank_points(&self) -> i64 {
+        self.points - self.rank_reset_points
+    }
 }
 
 impl From<UserRaw> for User {
@@ -42,6 +63,12 @@ impl From<UserRaw> for User {
             email: value.email,
             points: value.points,
             rank: value.rank.into(),
+            announce_rank_ups: value.announce_rank_ups,
+            created_at: Timestamp::from_unix_timestamp(value.created_at)
+                .expect("invalid timestamp returned from database"),
+            verified_at: value.verified_at.map(|verified_at| Timestamp::from_unix_timestamp(verified_at)
+                .expect("invalid timestamp returned from database")),
+            rank_reset_points: value.rank_reset_points,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,28 @@
+use serenity::all::{Timestamp, UserId};
+
+#[derive(Debug, Clone)]
+pub struct VoiceSessionRaw {
+    pub id: i64,
+    pub user_id: i64,
+    pub counted_until: i64,
+}
+
+/// Time a user is spending in a voice channel worth points
+#[derive(Debug, Clone)]
+pub struct VoiceSession {
+    pub id: i64,
+    pub user_id: UserId,
+    /// Time in voice before this has already been checked for points
+    pub counted_until: Timestamp,
+}
+
+impl From<VoiceSessionRaw> for VoiceSession {
+    fn from(value: VoiceSessionRaw) -> Self {
+        VoiceSession {
+            id: value.id,
+            user_id: UserId::new(value.user_id as u64),
+            counted_until: Timestamp::from_unix_timestamp(value.counted_until)
+                .expect("invalid timestamp returned from database"),
+        }
+    }
+}
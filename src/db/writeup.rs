@@ -0,0 +1,51 @@
+use serenity::all::{MessageId, UserId};
+
+use super::ApprovalStatus;
+
+#[derive(Debug, Clone)]
+pub struct WriteupRaw {
+    pub id: i64,
+    pub challenge_id: i64,
+    pub user_id: i64,
+    pub url: String,
+    pub approval_message_id: Option<i64>,
+    pub approval_status: i64,
+}
+
+impl From<Writeup> for WriteupRaw {
+    fn from(value: Writeup) -> Self {
+        WriteupRaw {
+            id: value.id,
+            challenge_id: value.challenge_id,
+            user_id: value.user_id.get() as i64,
+            url: value.url,
+            approval_message_id: value.approval_message_id.map(|id| id.get() as i64),
+            approval_status: value.approval_status as i64,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Writeup {
+    pub id: i64,
+    pub challenge_id: i64,
+    pub user_id: UserId,
+    pub url: String,
+    /// Message officers use to approve bonus points for the writeup
+    pub approval_message_id: Option<MessageId>,
+    pub approval_status: ApprovalStatus,
+}
+
+impl From<WriteupRaw> for Writeup {
+    fn from(value: WriteupRaw) -> Self {
+        Writeup {
+            id: value.id,
+            challenge_id: value.challenge_id,
+            user_id: UserId::new(value.user_id as u64),
+            url: value.url,
+            approval_message_id: value.approval_message_id.map(|id| MessageId::new(id as u64)),
+            approval_status: ApprovalStatus::from_repr(value.approval_status)
+                .expect("invalid approval status returned from database"),
+        }
+    }
+}
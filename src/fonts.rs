@@ -0,0 +1,16 @@
+//! Fonts shared by everything the bot draws
+
+use ab_glyph::Font;
+
+pub const FONT: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+pub const BOLD_FONT: &[u8] = include_bytes!("../fonts/DejaVuSans-Bold.ttf");
+
+/// Removes characters the font can't draw, like the emojis in rank names
+pub fn renderable_text(font: &impl Font, text: &str) -> String {
+    let text: String = text.chars()
+        .filter(|c| c.is_whitespace() || font.glyph_id(*c).0 != 0)
+        .collect();
+
+    // removed characters can leave behind double spaces
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
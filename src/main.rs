@@ -1,20 +1,35 @@
+mod achievements;
+mod bingo;
+mod charts;
 mod commands;
 mod config;
 mod db;
 mod email;
+mod fonts;
 mod logging;
+mod message_points;
+mod participation_points;
 mod points;
+mod rank_card;
+mod semester;
+mod stats_digest;
 
+use base64::prelude::*;
 use clap::Parser;
 use dotenvy::dotenv;
 use email::EmailClient;
 use logging::init_logging;
-use points::give_points;
+use message_points::{ChannelPath, MessageThrottle, PendingMessagePoints};
 use poise::{BoxFuture, FrameworkContext, FrameworkError};
 use serenity::all::{
     Channel, ClientBuilder, Context, CreateMessage, FullEvent, GatewayIntents, Interaction,
+    ShardManager,
+};
+use std::{
+    env,
+    path::PathBuf,
+    sync::{Arc, Mutex},
 };
-use std::{env, path::PathBuf};
 use tracing::{error, info};
 
 use commands::CommandContext;
@@ -23,11 +38,11 @@ use db::DbContext;
 
 /// Runs for every serenity event
 ///
-/// Currently needed for solve approve / reject buttons to work
+/// Currently needed for solve and writeup approve / reject buttons, reveal credentials buttons, bingo square buttons, and participation points to work
 fn event_handler<'a>(
     context: &'a Context,
     event: &'a FullEvent,
-    framework_context: FrameworkContext<'a, CommandContext, anyhow::Error>,
+    _framework_context: FrameworkContext<'a, CommandContext, anyhow::Error>,
     user_data: &'a CommandContext,
 ) -> BoxFuture<'a, Result<(), anyhow::Error>> {
     Box::pin(async move {
@@ -35,8 +50,25 @@ fn event_handler<'a>(
             interaction: Interaction::Component(component_interaction),
         } = event
         {
-            commands::solve::handle_approval_button(context, user_data, component_interaction)
-                .await?;
+            match component_interaction.data.custom_id.as_str() {
+                "accept" | "reject" => {
+                    commands::solve::handle_approval_button(context, user_data, component_interaction)
+                        .await?
+                }
+                "writeup_accept" | "writeup_reject" => {
+                    commands::writeup::handle_approval_button(context, user_data, component_interaction)
+                        .await?
+                }
+                id if id.starts_with(commands::credentials::REVEAL_BUTTON_PREFIX) => {
+                    commands::credentials::handle_reveal_button(context, user_data, component_interaction)
+                        .await?
+                }
+                id if id.starts_with(commands::bingo::SQUARE_BUTTON_PREFIX) => {
+                    commands::bingo::handle_square_button(context, user_data, component_interaction)
+                        .await?
+                }
+                _ => (),
+            }
         }
 
         if let FullEvent::Message { new_message } = event {
@@ -48,24 +80,64 @@ fn event_handler<'a>(
                     new_message.author.name, new_message.content
                 ),
                 Channel::Guild(channel) if channel.guild_id == config().server.guild_id => {
-                    let mut conn = framework_context.user_data().await.conn().await;
+                    let channel = ChannelPath::new(context, &channel);
 
                     // give points for sending messages
                     // this also gives points to the bot, this is intentinal
-                    give_points(
-                        context,
-                        &mut conn,
+                    let points = user_data.message_throttle.lock().unwrap().points_for_message(
                         new_message.author.id,
-                        config().ranks.points_per_message,
-                    )
-                    .await?;
+                        &channel,
+                        &new_message.content,
+                    );
+
+                    // the channel multiplier can need the database, so only look it up for messages that earned points
+                    let points = if points > 0 {
+                        let channel_multiplier =
+                            message_points::channel_multiplier(&user_data.db, &channel).await?;
+
+                        user_data
+                            .message_throttle
+                            .lock()
+                            .unwrap()
+                            .apply_multipliers(points, channel_multiplier)
+                    } else {
+                        0
+                    };
 
-                    conn.commit().await?;
+                    // points are saved in bulk later
+                    if points > 0 {
+                        let should_flush = user_data
+                            .pending_message_points
+                            .lock()
+                            .unwrap()
+                            .add(new_message.author.id, points);
+
+                        if should_flush {
+                            message_points::flush(
+                                context,
+                                &user_data.db,
+                                &user_data.pending_message_points,
+                            )
+                            .await?;
+                        }
+                    }
                 }
                 _ => (),
             }
         }
 
+        if let FullEvent::VoiceStateUpdate { new, .. } = event {
+            participation_points::handle_voice_state_update(context, user_data, new).await?;
+        }
+
+        if let FullEvent::GuildCreate { guild, .. } = event {
+            participation_points::resume_voice_sessions(user_data, guild).await?;
+        }
+
+        if let FullEvent::ReactionAdd { add_reaction } = event {
+            participation_points::handle_reaction_add(context, user_data, add_reaction).await?;
+        }
+
         if let FullEvent::GuildMemberAddition { new_member } = event {
             if new_member.guild_id == config().server.guild_id {
                 let message = CreateMessage::new().content(&config().server.join_dm_message);
@@ -78,11 +150,19 @@ fn event_handler<'a>(
     })
 }
 
+/// Commands whose arguments should not be logged
+const SECRET_COMMANDS: &[&str] = &["competition create", "competition credentials", "attendance checkin"];
+
 fn pre_command_handler<'a>(
     context: poise::Context<'a, CommandContext, anyhow::Error>,
 ) -> BoxFuture<'a, ()> {
     Box::pin(async move {
-        info!("Running command `{}`", context.invocation_string());
+        // don't leak credentials into the bot log channel
+        if SECRET_COMMANDS.contains(&context.command().qualified_name.as_str()) {
+            info!("Running command `/{}`", context.command().qualified_name);
+        } else {
+            info!("Running command `{}`", context.invocation_string());
+        }
     })
 }
 
@@ -127,6 +207,42 @@ fn error_handler<'a>(
     })
 }
 
+/// Waits for ctrl-c or SIGTERM, then saves everything kept in memory and stops the bot
+async fn shutdown_on_signal(
+    context: Context,
+    db: DbContext,
+    message_throttle: Arc<Mutex<MessageThrottle>>,
+    pending_message_points: Arc<Mutex<PendingMessagePoints>>,
+    shard_manager: Arc<ShardManager>,
+) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => (),
+            _ = terminate.recv() => (),
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    info!("shutting down the bot");
+
+    if let Err(e) = message_points::flush(&context, &db, &pending_message_points).await {
+        error!("failed to give message points on shutdown: {e}");
+    }
+
+    if let Err(e) = message_points::persist(&db, &message_throttle).await {
+        error!("failed to save message point limits on shutdown: {e}");
+    }
+
+    shard_manager.shutdown_all().await;
+}
+
 /// b01lers discord bot
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -155,6 +271,12 @@ async fn main() {
     let mailgun_token =
         env::var("MAILGUN_TOKEN").expect("No `MAILGUN_TOKEN` environment variable specified");
 
+    let credentials_key =
+        env::var("CREDENTIALS_KEY").expect("No `CREDENTIALS_KEY` environment variable specified");
+    let credentials_key = BASE64_STANDARD
+        .decode(credentials_key)
+        .expect("`CREDENTIALS_KEY` is not valid base64");
+
     let db = DbContext::connect(&database_url)
         .await
         .expect("failed to connect to database");
@@ -170,6 +292,13 @@ async fn main() {
                 commands::solve::quick_solve(),
                 commands::verify::verify(),
                 commands::stats::stats(),
+                commands::writeup::writeup(),
+                commands::note::note(),
+                commands::points::points(),
+                commands::ranks::ranks(),
+                commands::season::season(),
+                commands::attendance::attendance(),
+                commands::multiplier::multiplier(),
                 commands::misc::welcome(),
                 commands::misc::get_roles(),
                 commands::misc::dm(),
@@ -195,13 +324,54 @@ async fn main() {
 
                 info!("the bot has logged on");
 
+                // keep message point limits in memory, and save them every so often
+                let message_throttle = Arc::new(Mutex::new(MessageThrottle::load(&db).await?));
+                tokio::spawn(message_points::persist_periodically(
+                    db.clone(),
+                    message_throttle.clone(),
+                ));
+
+                // give out message points in bulk every so often
+                let pending_message_points = Arc::new(Mutex::new(PendingMessagePoints::default()));
+                tokio::spawn(message_points::flush_periodically(
+                    ctx.clone(),
+                    db.clone(),
+                    pending_message_points.clone(),
+                ));
+
+                // give points for time in voice every so often
+                tokio::spawn(participation_points::count_voice_periodically(ctx.clone(), db.clone()));
+
+                // post server stats to officers every week
+                tokio::spawn(stats_digest::post_weekly(ctx.clone(), db.clone()));
+
+                tokio::spawn(shutdown_on_signal(
+                    ctx.clone(),
+                    db.clone(),
+                    message_throttle.clone(),
+                    pending_message_points.clone(),
+                    framework.shard_manager().clone(),
+                ));
+
                 let email_client = EmailClient::new(mailgun_token);
-                Ok(CommandContext::new(db, email_client))
+                let command_context = CommandContext::new(
+                    db,
+                    email_client,
+                    &credentials_key,
+                    message_throttle,
+                    pending_message_points,
+                );
+
+                Ok(command_context)
             })
         })
         .build();
 
-    let intents = GatewayIntents::non_privileged().union(GatewayIntents::GUILD_MEMBERS);
+    // non privileged intents include voice states and reactions needed for participation points
+    // message content is privileged and has to be enabled in the developer portal, without it message points are never given
+    let intents = GatewayIntents::non_privileged()
+        .union(GatewayIntents::GUILD_MEMBERS)
+        .union(GatewayIntents::MESSAGE_CONTENT);
     let mut client = ClientBuilder::new(discord_token, intents)
         .framework(framework)
         .await
@@ -0,0 +1,346 @@
+//! Limits the points people can earn from messages, so spamming is not the best way to rank up
+//!
+//! Limits are kept in memory so messages don't each need a database transaction,
+//! and are periodically saved so they survive restarts.
+//! Message points are also collected in memory and given out in bulk.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serenity::all::{ChannelId, ChannelType, Context, GuildChannel, Timestamp, UserId};
+use tracing::error;
+
+use crate::config::config;
+use crate::db::{DbContext, MessagePointLimit, PointMultiplier, PointsReason, PointsUpdate};
+use crate::points::{check_rank_change_with, RankManager};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+fn current_day(now: i64) -> i64 {
+    now / SECONDS_PER_DAY
+}
+
+/// A channel with the channel and category it is inside of
+pub struct ChannelPath {
+    pub channel_id: ChannelId,
+    /// Channel a thread is in, or the category of a channel
+    pub parent_id: Option<ChannelId>,
+    /// Category the channel or the thread's channel is in
+    pub category_id: Option<ChannelId>,
+    pub is_thread: bool,
+}
+
+impl ChannelPath {
+    pub fn new(context: &Context, channel: &GuildChannel) -> Self {
+        let is_thread = matches!(channel.kind, ChannelType::PublicThread | ChannelType::PrivateThread);
+
+        // threads are inside a channel which is inside a category
+        let category_id = if is_thread {
+            channel.parent_id.and_then(|parent_id| context.cache.guild(channel.guild_id)
+                .and_then(|guild| guild.channels.get(&parent_id).and_then(|parent| parent.parent_id)))
+        } else {
+            channel.parent_id
+        };
+
+        ChannelPath {
+            channel_id: channel.id,
+            parent_id: channel.parent_id,
+            category_id,
+            is_thread,
+        }
+    }
+
+    /// Checks if the channel is `id`, or is inside of `id`
+    fn is_in(&self, id: ChannelId) -> bool {
+        self.channel_id == id || self.parent_id == Some(id) || self.category_id == Some(id)
+    }
+}
+
+pub struct MessageThrottle {
+    limits: HashMap<UserId, MessagePointLimit>,
+    /// Users whose limits changed since they were last saved
+    dirty: HashSet<UserId>,
+    /// Temporary multipliers started by officers
+    multipliers: Vec<PointMultiplier>,
+}
+
+impl MessageThrottle {
+    /// Loads today's message point limits from the database
+    pub async fn load(db: &DbContext) -> anyhow::Result<Self> {
+        let mut conn = db.try_conn().await?;
+        let day = current_day(Timestamp::now().unix_timestamp());
+
+        let limits = conn.get_message_point_limits(day).await?
+            .into_iter()
+            .map(|limit| (limit.user_id, limit))
+            .collect();
+
+        let multipliers = conn.get_active_point_multipliers().await?;
+
+        conn.commit().await?;
+
+        Ok(MessageThrottle {
+            limits,
+            dirty: HashSet::new(),
+            multipliers,
+        })
+    }
+
+    /// Replaces the temporary multipliers, should be called whenever they are changed in the database
+    pub fn set_multipliers(&mut self, multipliers: Vec<PointMultiplier>) {
+        self.multipliers = multipliers;
+    }
+
+    /// Gets the highest temporary multiplier which hasn't expired, multipliers don't stack
+    fn global_multiplier(&self) -> f64 {
+        let now = Timestamp::now();
+
+        self.multipliers.iter()
+            .filter(|multiplier| multiplier.is_active(now))
+            .map(|multiplier| multiplier.multiplier)
+            .fold(1.0, f64::max)
+    }
+
+    /// Gets how many points a message is worth after applying the anti farming rules, and counts them towards the user's limits
+    /// 
+    /// The daily cap limits points before multipliers, so a double points weekend really doubles the points that can be earned.
+    /// Use [`MessageThrottle::apply_multipliers`] to get the points that are given.
+    pub fn points_for_message(&mut self, user_id: UserId, channel: &ChannelPath, content: &str) -> i64 {
+        let rules = &config().message_points;
+
+        let is_ignored = rules.ignored_channels.iter()
+            .any(|id| channel.is_in(*id));
+
+        if is_ignored || content.trim().chars().count() < rules.min_length {
+            return 0;
+        }
+
+        let now = Timestamp::now().unix_timestamp();
+        let day = current_day(now);
+
+        let limit = self.limits.entry(user_id).or_insert(MessagePointLimit {
+            user_id,
+            day,
+            points_today: 0,
+            last_awarded_at: 0,
+        });
+
+        if now - limit.last_awarded_at < rules.cooldown_seconds {
+            return 0;
+        }
+
+        if limit.day != day {
+            limit.day = day;
+            limit.points_today = 0;
+        }
+
+        let points = std::cmp::min(
+            config().ranks.points_per_message,
+            rules.daily_cap - limit.points_today,
+        );
+
+        if points <= 0 {
+            return 0;
+        }
+
+        limit.points_today += points;
+        limit.last_awarded_at = now;
+        self.dirty.insert(user_id);
+
+        points
+    }
+
+    /// Multiplies points from [`MessageThrottle::points_for_message`] by the channel multiplier and any active multipliers
+    pub fn apply_multipliers(&self, points: i64, channel_multiplier: f64) -> i64 {
+        (points as f64 * channel_multiplier * self.global_multiplier()).round() as i64
+    }
+
+    /// Marks limits as changed again, so they are saved next time
+    fn restore_dirty(&mut self, user_ids: impl IntoIterator<Item = UserId>) {
+        self.dirty.extend(user_ids);
+    }
+
+    /// Takes the limits that changed since the last time they were taken
+    fn take_dirty(&mut self) -> Vec<MessagePointLimit> {
+        let dirty = std::mem::take(&mut self.dirty);
+
+        // forget about users from previous days, they won't be limited anymore
+        let day = current_day(Timestamp::now().unix_timestamp());
+        let changed = dirty.iter()
+            .filter_map(|user_id| self.limits.get(user_id).cloned())
+            .collect();
+
+        self.limits.retain(|_, limit| limit.day == day);
+
+        changed
+    }
+}
+
+/// Gets the multiplier for messages in a channel from the configured channel multipliers,
+/// and from whether the channel is a challenge thread of a competition which hasn't been archived
+///
+/// This can open a transaction, so only call it for messages which earned points.
+pub async fn channel_multiplier(db: &DbContext, channel: &ChannelPath) -> anyhow::Result<f64> {
+    let rules = &config().message_points;
+
+    let configured_multiplier = |id: Option<ChannelId>| id
+        .and_then(|id| rules.channel_multipliers.get(&id).copied());
+
+    let mut multiplier = configured_multiplier(Some(channel.channel_id))
+        .or_else(|| configured_multiplier(channel.parent_id))
+        .or_else(|| configured_multiplier(channel.category_id))
+        .unwrap_or(1.0);
+
+    // only look in the database for threads which could be challenges, so most messages don't need a transaction
+    if channel.is_thread && channel.category_id == Some(config().server.ctf_category_id) {
+        let mut conn = db.try_conn().await?;
+
+        if conn.get_challenge_by_channel_id(channel.channel_id).await.is_ok() {
+            multiplier *= rules.challenge_multiplier;
+        }
+
+        conn.commit().await?;
+    }
+
+    Ok(multiplier)
+}
+
+/// Saves message point limits that changed since the last save
+pub async fn persist(db: &DbContext, throttle: &Mutex<MessageThrottle>) -> anyhow::Result<()> {
+    let changed = throttle.lock().unwrap().take_dirty();
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let user_ids: Vec<UserId> = changed.iter().map(|limit| limit.user_id).collect();
+
+    // put the limits back if they couldn't be saved, so they are tried again next time
+    if let Err(e) = save_limits(db, changed).await {
+        throttle.lock().unwrap().restore_dirty(user_ids);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+async fn save_limits(db: &DbContext, limits: Vec<MessagePointLimit>) -> anyhow::Result<()> {
+    let mut conn = db.try_conn().await?;
+
+    for limit in limits {
+        conn.set_message_point_limit(limit).await?;
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
+
+/// Saves message point limits every `persist_interval_seconds`, forever
+pub async fn persist_periodically(db: DbContext, throttle: Arc<Mutex<MessageThrottle>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config().message_points.persist_interval_seconds));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = persist(&db, &throttle).await {
+            error!("failed to save message point limits: {e}");
+        }
+    }
+}
+
+/// Message points which have been earned but not saved yet
+#[derive(Default)]
+pub struct PendingMessagePoints {
+    points: HashMap<UserId, i64>,
+    message_count: usize,
+}
+
+impl PendingMessagePoints {
+    /// Adds points earned by a message
+    /// 
+    /// # Returns
+    /// 
+    /// Returns true if enough messages are pending that they should be flushed
+    pub fn add(&mut self, user_id: UserId, points: i64) -> bool {
+        *self.points.entry(user_id).or_default() += points;
+        self.message_count += 1;
+
+        self.message_count >= config().message_points.flush_threshold
+    }
+
+    fn take(&mut self) -> HashMap<UserId, i64> {
+        self.message_count = 0;
+        std::mem::take(&mut self.points)
+    }
+
+    /// Puts back points which were taken but couldn't be saved, keeping any points earned since
+    fn restore(&mut self, points: HashMap<UserId, i64>) {
+        for (user_id, points) in points {
+            *self.points.entry(user_id).or_default() += points;
+        }
+    }
+}
+
+/// Gives out all pending message points, then checks rank changes once for each user
+pub async fn flush(context: &Context, db: &DbContext, pending: &Mutex<PendingMessagePoints>) -> anyhow::Result<()> {
+    let points = pending.lock().unwrap().take();
+
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    let points_updates = match save_points(db, &points).await {
+        Ok(points_updates) => points_updates,
+        Err(e) => {
+            // put the points back so they are given on the next flush
+            pending.lock().unwrap().restore(points);
+            return Err(e);
+        },
+    };
+
+    // rank changes are checked after points are saved, so a user who left the server can't cause everyone's points to be lost
+    let mut conn = db.try_conn().await?;
+
+    // everyone is checked against the same cutoffs, instead of calculating them again for each user
+    let rank_manager = RankManager::new(&mut conn).await?;
+
+    for points_update in points_updates {
+        let user_id = points_update.user_id;
+
+        if let Err(e) = check_rank_change_with(context, &mut conn, &rank_manager, points_update).await {
+            error!("failed to check rank change for {user_id}: {e}");
+        }
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
+
+async fn save_points(db: &DbContext, points: &HashMap<UserId, i64>) -> anyhow::Result<Vec<PointsUpdate>> {
+    let mut conn = db.try_conn().await?;
+
+    let mut points_updates = Vec::new();
+    for (user_id, points) in points {
+        points_updates.push(conn.give_user_points(*user_id, *points, &PointsReason::Message, None).await?);
+    }
+
+    conn.commit().await?;
+
+    Ok(points_updates)
+}
+
+/// Gives out pending message points every `flush_interval_seconds`, forever
+pub async fn flush_periodically(context: Context, db: DbContext, pending: Arc<Mutex<PendingMessagePoints>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config().message_points.flush_interval_seconds));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = flush(&context, &db, &pending).await {
+            error!("failed to give message points: {e}");
+        }
+    }
+}
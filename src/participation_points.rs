@@ -0,0 +1,211 @@
+//! Gives points for participating outside of text chat, like voice calls during competitions and reactions
+//!
+//! Voice sessions are saved in the database, and time in voice is counted every few minutes,
+//! so only time while a competition is running is worth points.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serenity::all::{Context, Guild, Reaction, Timestamp, UserId, VoiceState};
+use tracing::error;
+
+use crate::commands::CommandContext;
+use crate::config::config;
+use crate::db::{DbContext, DbConn, PointsReason, VoiceSession};
+use crate::points::give_points;
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How often time in voice is counted, a competition has to be running when it is counted for it to be worth points
+const VOICE_COUNT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Checks if the voice state is in a voice channel worth points
+fn is_in_voice(voice_state: &VoiceState) -> bool {
+    // deafened users are probably not paying attention to the call
+    !voice_state.self_deaf && voice_state.channel_id
+        .is_some_and(|channel_id| config().participation_points.voice_channels.contains(&channel_id))
+}
+
+/// Checks if there is a competition channel which hasn't been archived yet
+fn is_competition_running(context: &Context) -> bool {
+    let server = &config().server;
+
+    context.cache.guild(server.guild_id)
+        .is_some_and(|guild| guild.channels.values()
+            .any(|channel| channel.parent_id == Some(server.ctf_category_id)))
+}
+
+/// Gives points for the whole minutes in the session which haven't been counted yet, if a competition is running,
+/// up to the daily voice cap
+async fn count_voice_session(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    session: &VoiceSession,
+    competition_running: bool,
+) -> anyhow::Result<()> {
+    let Some(points_per_minute) = config().participation_points.points_per_voice_minute else {
+        return Ok(());
+    };
+
+    let now = Timestamp::now().unix_timestamp();
+    let counted_until = session.counted_until.unix_timestamp();
+
+    let minutes = (now - counted_until) / SECONDS_PER_MINUTE;
+    if minutes <= 0 {
+        return Ok(());
+    }
+
+    // leftover seconds are counted next time
+    db.set_voice_session_counted_until(session.id, counted_until + minutes * SECONDS_PER_MINUTE).await?;
+
+    if !competition_running {
+        return Ok(());
+    }
+
+    let day_start = now - now % SECONDS_PER_DAY;
+    let points_today = db.get_voice_points_since(session.user_id, day_start).await?;
+
+    let points = std::cmp::min(
+        minutes * points_per_minute,
+        config().participation_points.voice_daily_cap - points_today,
+    );
+
+    if points <= 0 {
+        return Ok(());
+    }
+
+    give_points(
+        context,
+        db,
+        session.user_id,
+        points,
+        &PointsReason::Voice { session_id: Some(session.id) },
+        None,
+    ).await?;
+
+    Ok(())
+}
+
+/// Starts a voice session when a user joins a voice channel which is worth points, and ends it when they leave
+pub async fn handle_voice_state_update(context: &Context, cmd_context: &CommandContext, voice_state: &VoiceState) -> anyhow::Result<()> {
+    if config().participation_points.points_per_voice_minute.is_none() {
+        return Ok(());
+    }
+
+    if voice_state.guild_id != Some(config().server.guild_id) {
+        return Ok(());
+    }
+
+    let mut conn = cmd_context.conn().await;
+
+    if is_in_voice(voice_state) {
+        conn.start_voice_session(voice_state.user_id).await?;
+    } else if let Some(session) = conn.get_open_voice_session(voice_state.user_id).await? {
+        count_voice_session(context, &mut conn, &session, is_competition_running(context)).await?;
+        conn.end_voice_session(session.id).await?;
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
+
+/// Matches the saved voice sessions to who is in voice when the bot connects
+///
+/// People who left while the bot was offline have their session ended without points for the time they were gone,
+/// and people who joined while the bot was offline start a session.
+pub async fn resume_voice_sessions(cmd_context: &CommandContext, guild: &Guild) -> anyhow::Result<()> {
+    if config().participation_points.points_per_voice_minute.is_none() || guild.id != config().server.guild_id {
+        return Ok(());
+    }
+
+    let in_voice: HashSet<UserId> = guild.voice_states.values()
+        .filter(|voice_state| is_in_voice(voice_state))
+        .map(|voice_state| voice_state.user_id)
+        .collect();
+
+    let mut conn = cmd_context.conn().await;
+
+    for session in conn.get_open_voice_sessions().await? {
+        if !in_voice.contains(&session.user_id) {
+            conn.end_voice_session(session.id).await?;
+        }
+    }
+
+    for user_id in in_voice {
+        conn.start_voice_session(user_id).await?;
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
+
+async fn count_voice_sessions(context: &Context, db: &DbContext) -> anyhow::Result<()> {
+    let mut conn = db.try_conn().await?;
+    let competition_running = is_competition_running(context);
+
+    for session in conn.get_open_voice_sessions().await? {
+        count_voice_session(context, &mut conn, &session, competition_running).await?;
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
+
+/// Gives points for time in voice every `VOICE_COUNT_INTERVAL`, forever
+pub async fn count_voice_periodically(context: Context, db: DbContext) {
+    let mut interval = tokio::time::interval(VOICE_COUNT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = count_voice_sessions(&context, &db).await {
+            error!("failed to give voice points: {e}");
+        }
+    }
+}
+
+/// Gives points to the author of a message when someone else reacts to it for the first time
+///
+/// The person who reacted is saved as the actor, so there is one ledger entry for each person who reacted to a message.
+pub async fn handle_reaction_add(context: &Context, cmd_context: &CommandContext, reaction: &Reaction) -> anyhow::Result<()> {
+    let Some(points) = config().participation_points.points_per_reaction else {
+        return Ok(());
+    };
+
+    if reaction.guild_id != Some(config().server.guild_id) {
+        return Ok(());
+    }
+
+    let (Some(author_id), Some(user_id)) = (reaction.message_author_id, reaction.user_id) else {
+        return Ok(());
+    };
+
+    let is_bot = reaction.member.as_ref().is_some_and(|member| member.user.bot);
+
+    // reacting to your own messages is not worth points
+    if author_id == user_id || is_bot {
+        return Ok(());
+    }
+
+    let mut conn = cmd_context.conn().await;
+
+    // removing and adding a reaction again, or adding a different reaction, isn't worth more points
+    if conn.record_reaction(reaction.message_id, user_id).await? {
+        give_points(
+            context,
+            &mut conn,
+            author_id,
+            points,
+            &PointsReason::Reaction { message_id: reaction.message_id.get() as i64 },
+            Some(user_id),
+        ).await?;
+    }
+
+    conn.commit().await?;
+
+    Ok(())
+}
@@ -1,46 +1,124 @@
-use crate::{commands::{add_role_to_user, remove_role_from_user}, config::config, db::{DbConn, PointsUpdate}};
+use crate::{commands::{add_role_to_user, remove_role_from_user}, config::{config, CutoffMode, RankPolicy}, db::{ChallengeType, DbConn, PointsReason, PointsUpdate}, rank_card::RankCard};
 
-use serenity::all::{Context, CreateMessage, UserId};
+use std::time::Duration;
 
-pub async fn get_point_cutoffs(db: &mut DbConn<'_>) -> anyhow::Result<Vec<i64>> {
-    let mut max_score = db.get_users_by_points(1)
-        .await?
-        .first()
-        .unwrap()
-        .points;
+use serenity::all::{Context, CreateAttachment, CreateMessage, Timestamp, UserId};
+use strum::IntoEnumIterator;
+use tracing::error;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
 
+/// How long to wait for an avatar before announcing a rank up without it
+const AVATAR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Gets the points needed for each rank, lowest rank first
+pub async fn get_point_cutoffs(db: &mut DbConn<'_>) -> anyhow::Result<Vec<i64>> {
     let rank_count = config().ranks.rank_count();
 
-    let mut cutoffs = vec![0; rank_count];
+    let cutoffs = match &config().ranks.cutoffs {
+        CutoffMode::Geometric { ratio } => {
+            let mut score = get_max_score(db).await?;
+            let mut cutoffs = vec![0; rank_count];
 
-    for i in (0..rank_count).rev() {
-        cutoffs[i] = max_score;
-        max_score = (max_score as f64 * 0.75) as i64;
-    }
+            for i in (0..rank_count).rev() {
+                cutoffs[i] = score;
+                score = (score as f64 * ratio) as i64;
+            }
+
+            cutoffs
+        },
+        CutoffMode::Linear => {
+            let max_score = get_max_score(db).await?;
+
+            (1..=rank_count)
+                .map(|i| max_score * i as i64 / rank_count as i64)
+                .collect()
+        },
+        CutoffMode::Absolute { thresholds } => {
+            config().ranks.rank_names
+                .iter()
+                .map(|rank_name| thresholds.get(rank_name)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("No point threshold configured for rank `{rank_name}`")))
+                .collect::<Result<_, _>>()?
+        },
+        CutoffMode::Percentile { active_days } => {
+            let active_since = Timestamp::now().unix_timestamp() - active_days * SECONDS_PER_DAY;
+            let points = db.get_active_user_points(active_since).await?;
+
+            // nobody has been active, so nobody can be ranked
+            if points.is_empty() {
+                vec![i64::MAX; rank_count]
+            } else {
+                (0..rank_count)
+                    .map(|i| points[i * points.len() / rank_count])
+                    .collect()
+            }
+        },
+    };
+
+    // cutoffs are all 0 when nobody has points yet, which would give everyone the top rank
+    let cutoffs = cutoffs.into_iter()
+        .map(|cutoff| cutoff.max(1))
+        .collect();
 
     Ok(cutoffs)
 }
 
-struct RankManager {
+async fn get_max_score(db: &mut DbConn<'_>) -> anyhow::Result<i64> {
+    db.get_max_rank_points().await
+}
+
+/// Explains how the rank cutoffs are calculated
+pub fn cutoff_mode_description() -> String {
+    match &config().ranks.cutoffs {
+        CutoffMode::Geometric { ratio } => format!(
+            "The top rank needs the highest score on the server, and each rank below needs {}% of the points of the rank above it.",
+            (ratio * 100.0).round(),
+        ),
+        CutoffMode::Linear => "Ranks are evenly spaced between 0 points and the highest score on the server.".to_string(),
+        CutoffMode::Absolute { .. } => "Each rank needs a fixed number of points.".to_string(),
+        CutoffMode::Percentile { active_days } => format!(
+            "Ranks are evenly split between everyone who earned points in the last {active_days} days, so each rank has about the same number of people.",
+        ),
+    }
+}
+
+pub struct RankManager {
     point_cutoffs: Vec<i64>,
 }
 
 impl RankManager {
-    async fn new(db: &mut DbConn<'_>) -> anyhow::Result<Self> {
+    pub async fn new(db: &mut DbConn<'_>) -> anyhow::Result<Self> {
         Ok(RankManager {
             point_cutoffs: get_point_cutoffs(db).await?,
         })
     }
 
-    fn rank_for_points(&self, points: i64) -> Rank {
-        match self.point_cutoffs.binary_search(&points) {
-            Ok(i) => Rank::Rank(i),
-            Err(i) => if i == 0 {
-                // the points is less than the lowest rank
-                Rank::Unranked
-            } else {
-                Rank::Rank(i - 1)
-            }
+    pub fn rank_for_points(&self, points: i64) -> Rank {
+        // cutoffs can have duplicates, so find the highest rank with a cutoff at or below the points
+        match self.point_cutoffs.partition_point(|cutoff| *cutoff <= points) {
+            // the points is less than the lowest rank
+            0 => Rank::Unranked,
+            i => Rank::Rank(i - 1),
+        }
+    }
+
+    /// Gets the points needed for a rank, `None` if unranked or the rank doesn't exist
+    pub fn cutoff(&self, rank: Rank) -> Option<i64> {
+        match rank {
+            Rank::Unranked => None,
+            Rank::Rank(i) => self.point_cutoffs.get(i).copied(),
+        }
+    }
+
+    /// Gets the rank a user with `points` should have according to the rank policy, given their current rank
+    pub fn resynced_rank(&self, points: i64, current_rank: Rank) -> Rank {
+        let rank = self.rank_for_points(points);
+
+        match config().ranks.rank_policy {
+            RankPolicy::Sticky => std::cmp::max(rank, current_rank),
+            RankPolicy::Recomputed => rank,
         }
     }
 }
@@ -78,47 +156,221 @@ impl Into<Option<i64>> for Rank {
     }
 }
 
-pub async fn check_rank_up(context: &Context, db: &mut DbConn<'_>, points_update: PointsUpdate) -> anyhow::Result<()> {
+/// Promotes the user if they earned a new rank, or demotes them if they should lose their rank according to the rank policy
+/// 
+/// Roles are changed and rank ups are announced after `db` is committed.
+pub async fn check_rank_change(context: &Context, db: &mut DbConn<'_>, points_update: PointsUpdate) -> anyhow::Result<()> {
     let rank_manager = RankManager::new(db).await?;
 
-    // check max of current old points and rank, they might have earned a rank in the past
-    let old_rank = std::cmp::max(
-        rank_manager.rank_for_points(points_update.old_points),
-        points_update.old_rank,
-    );
-    let new_rank = rank_manager.rank_for_points(points_update.new_points);
+    check_rank_change_with(context, db, &rank_manager, points_update).await
+}
 
-    if new_rank > old_rank {
-        let user = points_update.user_id.to_user(context).await?;
+/// Same as [`check_rank_change`], but with cutoffs which were already calculated, for checking many users at once
+pub async fn check_rank_change_with(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    rank_manager: &RankManager,
+    points_update: PointsUpdate,
+) -> anyhow::Result<()> {
+    let rank = rank_manager.rank_for_points(points_update.new_points);
 
-        if let Some(new_rank_name) = new_rank.rank_name() {
-            add_role_to_user(context, points_update.user_id, new_rank_name).await?;
+    let (old_rank, new_rank) = match config().ranks.rank_policy {
+        RankPolicy::Recomputed => (points_update.old_rank, rank),
+        // only demote when points are lost, ranks are kept when other people raise the cutoffs
+        RankPolicy::Sticky if points_update.new_points < points_update.old_points => {
+            (points_update.old_rank, std::cmp::min(rank, points_update.old_rank))
+        },
+        RankPolicy::Sticky => {
+            // check max of current old points and rank, they might have earned a rank in the past
+            let old_rank = std::cmp::max(
+                rank_manager.rank_for_points(points_update.old_points),
+                points_update.old_rank,
+            );
 
-            // create rank up message
-            let message = CreateMessage::new()
-                .content(format!("{} has reached the rank {}!", user.name, new_rank_name));
+            (old_rank, std::cmp::max(rank, old_rank))
+        },
+    };
 
-            config().server.rank_up_channel.send_message(context, message).await?;
-        }
+    if new_rank == old_rank {
+        return Ok(());
+    }
+
+    let user_id = points_update.user_id;
+    db.set_rank(user_id, new_rank).await?;
 
-        db.set_rank(points_update.user_id, new_rank).await?;
+    let rank_up = if new_rank > old_rank {
+        get_rank_up(db, rank_manager, user_id, old_rank, new_rank).await?
+    } else {
+        None
+    };
 
-        if let Some(old_rank_name) = old_rank.rank_name() {
-            remove_role_from_user(context, points_update.user_id, old_rank_name).await?; 
+    let context = context.clone();
+    db.after_commit(async move {
+        if let Err(e) = change_rank_roles(&context, user_id, old_rank, new_rank).await {
+            error!("failed to change rank roles for {user_id}: {e}");
         }
+
+        // a failed announcement shouldn't undo the rank change
+        if let Some(rank_up) = rank_up {
+            if let Err(e) = announce_rank_up(&context, rank_up).await {
+                error!("failed to announce rank up for {user_id}: {e}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Everything from the database needed to announce a rank up
+struct RankUp {
+    user_id: UserId,
+    old_rank_name: Option<String>,
+    new_rank_name: String,
+    points: i64,
+    rank_cutoff: i64,
+    next_rank: Option<(String, i64)>,
+    solve_counts: Vec<(String, usize)>,
+}
+
+/// Gets the rank up to announce, `None` if the user opted out of announcements
+async fn get_rank_up(
+    db: &mut DbConn<'_>,
+    rank_manager: &RankManager,
+    user_id: UserId,
+    old_rank: Rank,
+    new_rank: Rank,
+) -> anyhow::Result<Option<RankUp>> {
+    let Some(new_rank_name) = new_rank.rank_name() else {
+        return Ok(None);
+    };
+
+    let db_user = db.get_user_by_id(user_id).await?;
+    if !db_user.announce_rank_ups {
+        return Ok(None);
     }
 
+    let solves = db.get_solved_challenges_for_user(user_id).await?;
+    let solve_counts = ChallengeType::iter()
+        .map(|category| (category.to_string(), solves.iter().filter(|solve| solve.category == category).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+
+    let next_rank = match new_rank {
+        Rank::Rank(i) => Rank::Rank(i + 1),
+        Rank::Unranked => Rank::Rank(0),
+    };
+
+    Ok(Some(RankUp {
+        user_id,
+        old_rank_name: old_rank.rank_name().map(str::to_string),
+        new_rank_name: new_rank_name.to_string(),
+        points: db_user.rank_points(),
+        rank_cutoff: rank_manager.cutoff(new_rank).unwrap_or_default(),
+        // check the cutoff first, there is no rank name above the highest rank
+        next_rank: rank_manager.cutoff(next_rank)
+            .and_then(|cutoff| next_rank.rank_name().map(|name| (name.to_string(), cutoff))),
+        solve_counts,
+    }))
+}
+
+/// Posts a rank card in the rank up channel
+async fn announce_rank_up(context: &Context, rank_up: RankUp) -> anyhow::Result<()> {
+    let user = rank_up.user_id.to_user(context).await?;
+
+    // a missing avatar shouldn't stop the announcement
+    let avatar = match fetch_avatar(&user.static_face()).await {
+        Ok(avatar) => Some(avatar),
+        Err(e) => {
+            error!("failed to get avatar for {}: {e}", rank_up.user_id);
+            None
+        },
+    };
+
+    let card = RankCard {
+        username: user.name.clone(),
+        avatar,
+        old_rank_name: rank_up.old_rank_name,
+        new_rank_name: rank_up.new_rank_name.clone(),
+        points: rank_up.points,
+        rank_cutoff: rank_up.rank_cutoff,
+        next_rank: rank_up.next_rank,
+        solve_counts: rank_up.solve_counts,
+    };
+
+    let message = CreateMessage::new()
+        .content(format!("{} has reached the rank {}!", user.name, rank_up.new_rank_name))
+        .add_file(CreateAttachment::bytes(card.render_png_bytes()?, "rank_card.png"));
+
+    config().server.rank_up_channel.send_message(context, message).await?;
+
     Ok(())
 }
 
-pub async fn give_points(context: &Context, db: &mut DbConn<'_>, user_id: UserId, points: i64) -> anyhow::Result<()> {
-    let points_update = db.give_user_points(user_id, points).await?;
+async fn fetch_avatar(url: &str) -> anyhow::Result<Vec<u8>> {
+    let client = reqwest::Client::builder()
+        .timeout(AVATAR_TIMEOUT)
+        .build()?;
 
-    check_rank_up(context, db, points_update).await?;
+    let bytes = client.get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(bytes.to_vec())
+}
+
+/// Swaps the user's old rank role for the new one
+async fn change_rank_roles(context: &Context, user_id: UserId, old_rank: Rank, new_rank: Rank) -> anyhow::Result<()> {
+    if let Some(new_rank_name) = new_rank.rank_name() {
+        add_role_to_user(context, user_id, new_rank_name).await?;
+    }
+
+    if let Some(old_rank_name) = old_rank.rank_name() {
+        remove_role_from_user(context, user_id, old_rank_name).await?; 
+    }
+
+    Ok(())
+}
+
+/// Gives the user points, recording the reason and the user who gave them, and changes their rank if necessary
+pub async fn give_points(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    user_id: UserId,
+    points: i64,
+    reason: &PointsReason,
+    actor_id: Option<UserId>,
+) -> anyhow::Result<()> {
+    let points_update = db.give_user_points(user_id, points, reason, actor_id).await?;
+
+    check_rank_change(context, db, points_update).await?;
 
     Ok(())
 }
 
+/// Sets the user's points to exactly `points` as a manual change by `actor_id`, and changes their rank if necessary
+///
+/// Returns the change in points, or `None` if the user already had `points` points.
+pub async fn set_points(
+    context: &Context,
+    db: &mut DbConn<'_>,
+    user_id: UserId,
+    points: i64,
+    note: &str,
+    actor_id: UserId,
+) -> anyhow::Result<Option<i64>> {
+    let Some(points_update) = db.set_user_points(user_id, points, note, actor_id).await? else {
+        return Ok(None);
+    };
+
+    let delta = points_update.new_points - points_update.old_points;
+    check_rank_change(context, db, points_update).await?;
+
+    Ok(Some(delta))
+}
+
 /// Converts points to a string to be displayed
 /// 
 /// Points are displayed factor of 10 less with a decimal place
@@ -0,0 +1,135 @@
+//! Draws the card posted when someone ranks up
+
+use std::io::Cursor;
+
+use ab_glyph::{FontRef, PxScale};
+use image::{imageops::{overlay, FilterType}, DynamicImage, ImageFormat, Rgba, RgbaImage};
+use imageproc::{drawing::{draw_filled_rect_mut, draw_text_mut}, rect::Rect};
+
+use crate::fonts::{renderable_text, BOLD_FONT, FONT};
+use crate::points::points_to_string;
+
+const CARD_SIZE: (u32, u32) = (800, 260);
+const AVATAR_SIZE: u32 = 170;
+/// Left edge of everything right of the avatar
+const TEXT_X: i32 = 240;
+const PROGRESS_BAR_WIDTH: u32 = 520;
+const PROGRESS_BAR_HEIGHT: u32 = 24;
+
+const BACKGROUND: Rgba<u8> = Rgba([30, 31, 34, 255]);
+/// b01lers red, same as embeds
+const ACCENT: Rgba<u8> = Rgba([0xc2, 0x20, 0x26, 255]);
+const PROGRESS_BAR_BACKGROUND: Rgba<u8> = Rgba([64, 66, 73, 255]);
+const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const SECONDARY_TEXT: Rgba<u8> = Rgba([181, 186, 193, 255]);
+
+/// Everything shown on a rank up card
+pub struct RankCard {
+    pub username: String,
+    /// Png, jpeg, or webp avatar of the user, a blank circle is drawn if `None`
+    pub avatar: Option<Vec<u8>>,
+    pub old_rank_name: Option<String>,
+    pub new_rank_name: String,
+    pub points: i64,
+    /// Points needed for the new rank
+    pub rank_cutoff: i64,
+    /// Name and points needed for the next rank, `None` if the new rank is the highest
+    pub next_rank: Option<(String, i64)>,
+    /// Number of challenges solved in each category which has solves
+    pub solve_counts: Vec<(String, usize)>,
+}
+
+impl RankCard {
+    pub fn render_png_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let font = FontRef::try_from_slice(FONT)?;
+        let bold_font = FontRef::try_from_slice(BOLD_FONT)?;
+
+        let mut card = RgbaImage::from_pixel(CARD_SIZE.0, CARD_SIZE.1, BACKGROUND);
+        draw_filled_rect_mut(&mut card, Rect::at(0, 0).of_size(10, CARD_SIZE.1), ACCENT);
+
+        let avatar = self.avatar.as_ref()
+            .and_then(|avatar| image::load_from_memory(avatar).ok());
+        let avatar_y = (CARD_SIZE.1 - AVATAR_SIZE) / 2;
+        overlay(&mut card, &circle_avatar(avatar), 40, avatar_y.into());
+
+        let username = renderable_text(&bold_font, &self.username);
+        draw_text_mut(&mut card, TEXT, TEXT_X, 30, PxScale::from(40.0), &bold_font, &username);
+
+        let rank_change = match &self.old_rank_name {
+            Some(old_rank_name) => format!("{} → {}", old_rank_name, self.new_rank_name),
+            None => format!("Reached {}", self.new_rank_name),
+        };
+        let rank_change = renderable_text(&font, &rank_change);
+        draw_text_mut(&mut card, ACCENT, TEXT_X, 85, PxScale::from(28.0), &font, &rank_change);
+
+        self.draw_progress_bar(&mut card, &font);
+
+        let solves = if self.solve_counts.is_empty() {
+            "No challenges solved yet".to_string()
+        } else {
+            let total: usize = self.solve_counts.iter().map(|(_, count)| count).sum();
+            let categories = self.solve_counts.iter()
+                .map(|(category, count)| format!("{category} {count}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            format!("{total} solves: {categories}")
+        };
+        draw_text_mut(&mut card, SECONDARY_TEXT, TEXT_X, 210, PxScale::from(20.0), &font, &solves);
+
+        let mut out = Vec::new();
+        DynamicImage::ImageRgba8(card).write_to(&mut Cursor::new(&mut out), ImageFormat::Png)?;
+
+        Ok(out)
+    }
+
+    fn draw_progress_bar(&self, card: &mut RgbaImage, font: &FontRef) {
+        let bar_y = 140;
+
+        let (progress, label) = match &self.next_rank {
+            Some((next_rank_name, next_cutoff)) => {
+                let needed = (next_cutoff - self.rank_cutoff).max(1);
+                let progress = (self.points - self.rank_cutoff) as f64 / needed as f64;
+
+                (progress, format!(
+                    "{} / {} points to {}",
+                    points_to_string(self.points),
+                    points_to_string(*next_cutoff),
+                    next_rank_name,
+                ))
+            },
+            None => (1.0, format!("{} points, highest rank", points_to_string(self.points))),
+        };
+
+        let filled_width = (PROGRESS_BAR_WIDTH as f64 * progress.clamp(0.0, 1.0)) as u32;
+
+        draw_filled_rect_mut(card, Rect::at(TEXT_X, bar_y).of_size(PROGRESS_BAR_WIDTH, PROGRESS_BAR_HEIGHT), PROGRESS_BAR_BACKGROUND);
+        if filled_width > 0 {
+            draw_filled_rect_mut(card, Rect::at(TEXT_X, bar_y).of_size(filled_width, PROGRESS_BAR_HEIGHT), ACCENT);
+        }
+
+        let label = renderable_text(font, &label);
+        let label_y = bar_y + PROGRESS_BAR_HEIGHT as i32 + 8;
+        draw_text_mut(card, SECONDARY_TEXT, TEXT_X, label_y, PxScale::from(20.0), font, &label);
+    }
+}
+
+/// Crops the avatar to a circle, or draws a blank circle if there is no avatar
+fn circle_avatar(avatar: Option<DynamicImage>) -> RgbaImage {
+    let avatar = match avatar {
+        Some(avatar) => avatar.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Triangle).to_rgba8(),
+        None => RgbaImage::from_pixel(AVATAR_SIZE, AVATAR_SIZE, PROGRESS_BAR_BACKGROUND),
+    };
+
+    let radius = AVATAR_SIZE as f64 / 2.0;
+
+    RgbaImage::from_fn(AVATAR_SIZE, AVATAR_SIZE, |x, y| {
+        let distance = ((x as f64 + 0.5 - radius).powi(2) + (y as f64 + 0.5 - radius).powi(2)).sqrt();
+
+        if distance <= radius {
+            *avatar.get_pixel(x, y)
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
@@ -0,0 +1,59 @@
+use chrono::{Datelike, TimeZone, Utc};
+use poise::macros::ChoiceParameter;
+use serenity::all::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter, strum::Display)]
+pub enum Term {
+    #[name = "spring"]
+    Spring,
+    #[name = "summer"]
+    Summer,
+    #[name = "fall"]
+    Fall,
+}
+
+/// A school semester, spring is january through may, summer is june and july, and fall is august through december
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Semester {
+    pub term: Term,
+    pub year: i32,
+}
+
+impl Semester {
+    pub fn current() -> Self {
+        Self::from_timestamp(Timestamp::now())
+    }
+
+    pub fn from_timestamp(timestamp: Timestamp) -> Self {
+        let term = match timestamp.month() {
+            1..=5 => Term::Spring,
+            6..=7 => Term::Summer,
+            _ => Term::Fall,
+        };
+
+        Semester {
+            term,
+            year: timestamp.year(),
+        }
+    }
+
+    /// Gets midnight utc on the first day of the semester
+    pub fn start(&self) -> Timestamp {
+        let month = match self.term {
+            Term::Spring => 1,
+            Term::Summer => 6,
+            Term::Fall => 8,
+        };
+
+        Utc.with_ymd_and_hms(self.year, month, 1, 0, 0, 0)
+            .single()
+            .expect("first day of semester is a valid date")
+            .into()
+    }
+}
+
+impl std::fmt::Display for Semester {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.term, self.year)
+    }
+}
@@ -0,0 +1,54 @@
+//! Posts the `/stats server` numbers to a configured channel once a week
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serenity::all::{Context, CreateMessage};
+use tracing::error;
+
+use crate::commands::stats::server_stats_embed;
+use crate::config::{config, StatsDigestConfig};
+use crate::db::DbContext;
+
+/// Gets the next time after `now` the digest should be posted
+fn next_post_time(now: DateTime<Utc>, digest_config: &StatsDigestConfig) -> DateTime<Utc> {
+    let days_until = (digest_config.weekday + 7 - now.weekday().num_days_from_monday()) % 7;
+
+    let post_time = Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), digest_config.hour, 0, 0)
+        .single()
+        .expect("stats digest hour is checked when the config is loaded")
+        + Duration::days(days_until.into());
+
+    if post_time > now {
+        post_time
+    } else {
+        post_time + Duration::weeks(1)
+    }
+}
+
+async fn post_digest(context: &Context, db: &DbContext, digest_config: &StatsDigestConfig) -> anyhow::Result<()> {
+    let embed = server_stats_embed(&mut db.try_conn().await?, true).await?
+        .title("Weekly b01lers Server Stats");
+
+    digest_config.channel_id.send_message(context, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Posts the stats digest every week at the configured time, forever
+///
+/// Does nothing if the digest is not configured.
+pub async fn post_weekly(context: Context, db: DbContext) {
+    let Some(digest_config) = &config().stats_digest else {
+        return;
+    };
+
+    loop {
+        let now = Utc::now();
+        let wait = (next_post_time(now, digest_config) - now).to_std()
+            .expect("next stats digest is in the future");
+        tokio::time::sleep(wait).await;
+
+        if let Err(e) = post_digest(&context, &db, digest_config).await {
+            error!("failed to post stats digest: {e}");
+        }
+    }
+}